@@ -1,3 +1,7 @@
 pub mod certificate_controller;
 pub mod index;
+#[cfg(feature = "issuance")]
+pub mod issuer_controller;
 pub mod public_key_controller;
+pub mod receipt_controller;
+pub mod trust_list_controller;
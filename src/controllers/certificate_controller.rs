@@ -5,23 +5,179 @@ use rocket_okapi::openapi;
 use sea_orm_rocket::Connection;
 
 use crate::databases::pool::Db;
+use crate::dto::accept::AcceptFhir;
+use crate::dto::api_client::AllowedDisclosure;
+use crate::dto::image::ImageData;
 use crate::dto::raw::RawData;
+use crate::dto::request::verify_batch_request_dto::VerifyBatchRequestDto;
+use crate::dto::response::fhir_response_dto::FhirBundle;
 use crate::dto::response::hc1_response_dto::HC1ValidationResponseDto;
+use crate::dto::response::shc_response_dto::ShcValidationResponseDto;
+use crate::dto::response::vds_nc_response_dto::VdsNcValidationResponseDto;
+use crate::dto::response::verify_batch_response_dto::VerifyBatchResponseDto;
 use crate::responses::error_message::ErrorMessage;
 use crate::responses::generic_response::Responses;
+use crate::responses::negotiated_response::NegotiatedResponses;
 use crate::responses::success_messages::SuccessMessage;
-use crate::services::validator::certificate_service::verify_base45;
+use crate::services::disclosure::disclosure_profile::DisclosureProfile;
+use crate::services::receipt::receipt_service::{with_batch_receipts, with_receipt};
+use crate::services::validator::certificate_service::{
+    verify_base45, verify_base45_batch, verify_base45_image,
+};
+use crate::services::validator::fhir_service::negotiate_response;
+use crate::services::validator::shc_service::verify_shc;
+use crate::services::validator::vds_nc_service::verify_vds_nc;
 
 /// # Verify base45 HC1 health certificates
+///
+/// When `policy` is given, the business rules of that policy are evaluated against the
+/// certificate content and reported apart from the cryptographic verification.
+///
+/// With `format=fhir` or `Accept: application/fhir+json`, valid DDCC vaccination certificates
+/// are returned as a FHIR R4 Bundle (DDCC:VS document).
+///
+/// `disclosure` (`full`, `minimal` or `validity-only`) selects how much of the certificate
+/// content is returned, capped by the profile of the api client (`X-Api-Key` header).
+///
+/// With `receipt=true`, json results carry a `receipt` signed by the service (see
+/// `/receipts/public-key`) with the hash of the body, the checks, the verifying `kid` and the
+/// trust list version
 #[openapi(tag = "Verify From Base45")]
-#[post("/verify-b45", format = "text/plain", data = "<data>")]
+#[post(
+    "/verify-b45?<policy>&<format>&<disclosure>&<receipt>",
+    format = "text/plain",
+    data = "<data>"
+)]
 pub async fn verify_base45_certificate(
     connection: Connection<'_, Db>,
     data: RawData<'_>,
+    policy: Option<String>,
+    format: Option<String>,
+    disclosure: Option<DisclosureProfile>,
+    receipt: Option<bool>,
+    accept: AcceptFhir,
+    allowed_disclosure: AllowedDisclosure,
     limits: &Limits,
-) -> Responses<Json<SuccessMessage<HC1ValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+) -> NegotiatedResponses<
+    Json<SuccessMessage<HC1ValidationResponseDto>>,
+    Json<FhirBundle>,
+    Json<ErrorMessage<'static>>,
+> {
     limits.get("data").unwrap_or(1.megabytes());
     let data: &str = data.0;
     let db = connection.into_inner();
-    verify_base45(db, data.to_string()).await
+    let disclosure = DisclosureProfile::effective(disclosure, allowed_disclosure.0);
+    let response = verify_base45(db, data.to_string(), policy, disclosure).await;
+    let response = with_receipt(db, response, data.as_bytes(), receipt.unwrap_or(false)).await;
+    negotiate_response(response, format.as_deref() == Some("fhir") || accept.0)
+}
+
+/// # Verify SMART Health Cards (numeric "shc:/" QR payload)
+///
+/// Supports the same `disclosure` profiles and `receipt` as `/verify-b45`
+#[openapi(tag = "Verify SMART Health Cards")]
+#[post(
+    "/verify-shc?<disclosure>&<receipt>",
+    format = "text/plain",
+    data = "<data>"
+)]
+pub async fn verify_shc_certificate(
+    connection: Connection<'_, Db>,
+    data: RawData<'_>,
+    disclosure: Option<DisclosureProfile>,
+    receipt: Option<bool>,
+    allowed_disclosure: AllowedDisclosure,
+) -> Responses<Json<SuccessMessage<ShcValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+    let data: &str = data.0;
+    let db = connection.into_inner();
+    let disclosure = DisclosureProfile::effective(disclosure, allowed_disclosure.0);
+    let response = verify_shc(db, data.to_string(), disclosure).await;
+    with_receipt(db, response, data.as_bytes(), receipt.unwrap_or(false)).await
+}
+
+/// # Verify ICAO VDS-NC health proofs (JSON document as found in the barcode)
+///
+/// Supports the same `disclosure` profiles and `receipt` as `/verify-b45`
+#[openapi(tag = "Verify ICAO VDS-NC")]
+#[post(
+    "/verify-vds-nc?<disclosure>&<receipt>",
+    format = "text/plain",
+    data = "<data>"
+)]
+pub async fn verify_vds_nc_certificate(
+    connection: Connection<'_, Db>,
+    data: RawData<'_>,
+    disclosure: Option<DisclosureProfile>,
+    receipt: Option<bool>,
+    allowed_disclosure: AllowedDisclosure,
+) -> Responses<Json<SuccessMessage<VdsNcValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+    let data: &str = data.0;
+    let db = connection.into_inner();
+    let disclosure = DisclosureProfile::effective(disclosure, allowed_disclosure.0);
+    let response = verify_vds_nc(db, data.to_string(), disclosure).await;
+    with_receipt(db, response, data.as_bytes(), receipt.unwrap_or(false)).await
+}
+
+/// # Verify a batch of base45 HC1 health certificates
+///
+/// Supports the same `policy` and `disclosure` profiles as `/verify-b45`, the policy being
+/// evaluated on every item. With `receipt=true` every item result carries its own receipt,
+/// hashing the item `data`. Results are always json, FHIR output is only available for single
+/// certificates
+#[openapi(tag = "Verify From Base45")]
+#[post(
+    "/verify-b45/batch?<policy>&<disclosure>&<receipt>",
+    format = "json",
+    data = "<data>"
+)]
+pub async fn verify_base45_certificate_batch(
+    connection: Connection<'_, Db>,
+    data: Json<VerifyBatchRequestDto>,
+    policy: Option<String>,
+    disclosure: Option<DisclosureProfile>,
+    receipt: Option<bool>,
+    allowed_disclosure: AllowedDisclosure,
+) -> Responses<Json<SuccessMessage<VerifyBatchResponseDto>>, Json<ErrorMessage<'static>>> {
+    let db = connection.into_inner();
+    let disclosure = DisclosureProfile::effective(disclosure, allowed_disclosure.0);
+    let items = data.into_inner().items;
+    let receipt = receipt.unwrap_or(false);
+    // item bodies are only kept when they have to be hashed into receipts
+    let inputs: Vec<String> = if receipt {
+        items.iter().map(|item| item.data.clone()).collect()
+    } else {
+        vec![]
+    };
+    let response = verify_base45_batch(db, items, policy, disclosure).await;
+    with_batch_receipts(db, response, inputs, receipt).await
+}
+
+/// # Verify base45 HC1 health certificates from a QR code picture (png or jpeg)
+///
+/// Supports the same `policy`, FHIR output, `disclosure` profiles and `receipt` as
+/// `/verify-b45`; the receipt hashes the uploaded picture
+#[openapi(tag = "Verify From Base45")]
+#[post(
+    "/verify-image?<policy>&<format>&<disclosure>&<receipt>",
+    data = "<data>"
+)]
+pub async fn verify_image_certificate(
+    connection: Connection<'_, Db>,
+    data: ImageData,
+    policy: Option<String>,
+    format: Option<String>,
+    disclosure: Option<DisclosureProfile>,
+    receipt: Option<bool>,
+    accept: AcceptFhir,
+    allowed_disclosure: AllowedDisclosure,
+) -> NegotiatedResponses<
+    Json<SuccessMessage<HC1ValidationResponseDto>>,
+    Json<FhirBundle>,
+    Json<ErrorMessage<'static>>,
+> {
+    let db = connection.into_inner();
+    let disclosure = DisclosureProfile::effective(disclosure, allowed_disclosure.0);
+    let response = verify_base45_image(db, &data.0, policy, disclosure).await;
+    let response = with_receipt(db, response, &data.0, receipt.unwrap_or(false)).await;
+    negotiate_response(response, format.as_deref() == Some("fhir") || accept.0)
 }
@@ -42,13 +42,21 @@ pub fn stage() -> AdHoc {
                     ..Default::default()
                 }),
             )
+            // standard location of the JWK Set, outside of the versioned api
+            .mount(
+                "/.well-known",
+                routes![crate::controllers::public_key_controller::get_jwks],
+            )
             .attach(cors());
         let openapi_settings = rocket_okapi::settings::OpenApiSettings::default();
         // let custom_route_spec = (vec![], custom_openapi_spec());
         mount_endpoints_and_merged_docs! {
         building_rocket, "/api/v1".to_owned(), openapi_settings,
             "/certificates" => get_routes_and_docs(&openapi_settings),
+            "/certificates" => get_routes_and_docs_for_issuance(&openapi_settings),
             "/public-key" => get_routes_and_docs_for_public_key(&openapi_settings),
+            "/receipts" => get_routes_and_docs_for_receipts(&openapi_settings),
+            "/trust-list" => get_routes_and_docs_for_trust_list(&openapi_settings),
         };
         building_rocket
     })
@@ -57,14 +65,57 @@ pub fn stage() -> AdHoc {
 pub fn get_routes_and_docs(settings: &OpenApiSettings) -> (Vec<rocket::Route>, OpenApi) {
     openapi_get_routes_spec![
         settings:
-        crate::controllers::certificate_controller::verify_base45_certificate
+        crate::controllers::certificate_controller::verify_base45_certificate,
+        crate::controllers::certificate_controller::verify_base45_certificate_batch,
+        crate::controllers::certificate_controller::verify_image_certificate,
+        crate::controllers::certificate_controller::verify_shc_certificate,
+        crate::controllers::certificate_controller::verify_vds_nc_certificate
     ]
 }
 
 pub fn get_routes_and_docs_for_public_key(
     settings: &OpenApiSettings,
 ) -> (Vec<rocket::Route>, OpenApi) {
-    openapi_get_routes_spec![settings: crate::controllers::public_key_controller::get_all]
+    openapi_get_routes_spec![
+        settings:
+        crate::controllers::public_key_controller::get_all,
+        crate::controllers::public_key_controller::get_jwks,
+        crate::controllers::public_key_controller::get_by_kid
+    ]
+}
+
+pub fn get_routes_and_docs_for_receipts(
+    settings: &OpenApiSettings,
+) -> (Vec<rocket::Route>, OpenApi) {
+    openapi_get_routes_spec![settings: crate::controllers::receipt_controller::get_public_key]
+}
+
+pub fn get_routes_and_docs_for_trust_list(
+    settings: &OpenApiSettings,
+) -> (Vec<rocket::Route>, OpenApi) {
+    openapi_get_routes_spec![
+        settings:
+        crate::controllers::trust_list_controller::get_trust_list,
+        crate::controllers::trust_list_controller::get_trust_list_key,
+        crate::controllers::trust_list_controller::get_trust_list_changes
+    ]
+}
+
+#[cfg(feature = "issuance")]
+pub fn get_routes_and_docs_for_issuance(
+    settings: &OpenApiSettings,
+) -> (Vec<rocket::Route>, OpenApi) {
+    openapi_get_routes_spec![
+        settings: crate::controllers::issuer_controller::issue_base45_certificate
+    ]
+}
+
+/// issuance routes are only mounted when built with the `issuance` feature
+#[cfg(not(feature = "issuance"))]
+pub fn get_routes_and_docs_for_issuance(
+    _settings: &OpenApiSettings,
+) -> (Vec<rocket::Route>, OpenApi) {
+    (vec![], OpenApi::new())
 }
 
 fn cors() -> Cors {
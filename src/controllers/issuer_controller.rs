@@ -0,0 +1,23 @@
+use rocket::post;
+use rocket::serde::json::Json;
+use rocket_okapi::openapi;
+
+use crate::dto::response::hc1_response_dto::DdccCoreDataSet;
+use crate::dto::response::issue_hc1_response_dto::IssueHc1ResponseDto;
+use crate::responses::error_message::ErrorMessage;
+use crate::responses::generic_response::Responses;
+use crate::responses::success_messages::SuccessMessage;
+use crate::services::issuer::hc1_issuer_service::issue_base45;
+
+/// # Issue base45 HC1 health certificates (test vectors only)
+///
+/// Signs the DDCC core data set with the locally configured issuer key; only available when
+/// built with the `issuance` feature. `validity_days` defaults to 365
+#[openapi(tag = "Issue Base45")]
+#[post("/issue-b45?<validity_days>", format = "json", data = "<data>")]
+pub async fn issue_base45_certificate(
+    data: Json<DdccCoreDataSet>,
+    validity_days: Option<i64>,
+) -> Responses<Json<SuccessMessage<IssueHc1ResponseDto>>, Json<ErrorMessage<'static>>> {
+    issue_base45(data.into_inner(), validity_days).await
+}
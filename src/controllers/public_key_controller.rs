@@ -1,11 +1,20 @@
 use crate::databases::pool::Db;
-use crate::dto::response::public_key_response_dto::PublicKeyResponseDto;
+use crate::dto::cache_conditions::CacheConditions;
+use crate::dto::response::public_key_response_dto::{
+    JwksResponseDto, PublicKeyDetailResponseDto, PublicKeyResponseDto,
+};
+use crate::entities::public_key::filter::{
+    KeyType, PublicKeyFilter, PublicKeySort, PublicKeySource,
+};
+use crate::responses::cached_response::Cacheable;
 use crate::responses::error_message::ErrorMessage;
 use crate::responses::generic_response::Responses;
 use crate::responses::success_messages::SuccessMessage;
 use crate::services::public_key::data_interface::PublicKeyService;
 use crate::utils::utils::Utils;
 use crate::CONTROLLER_TRUSTED_REGISTRY;
+use chrono::Utc;
+use lacpass_verifier::country_code::normalize_country_code;
 use log::error;
 use rocket::get;
 use rocket::serde::json::Json;
@@ -13,38 +22,136 @@ use rocket_okapi::openapi;
 use sea_orm_rocket::Connection;
 use uuid::Uuid;
 
-/// # Return public keys
-#[openapi(tag = "Public keys")]
-#[get("/get-all?<page>&<results_per_page>")]
-pub async fn get_all(
-    connection: Connection<'_, Db>,
-    page: Option<u64>,
-    results_per_page: Option<u64>,
-) -> Responses<Json<SuccessMessage<PublicKeyResponseDto>>, Json<ErrorMessage<'static>>> {
+/// Returns the contract address (hex) and chain id of the public directory whose keys are exposed
+pub(crate) fn get_exposed_public_directory() -> Result<(String, String), Json<ErrorMessage<'static>>>
+{
     match CONTROLLER_TRUSTED_REGISTRY.get() {
         Some(tr) => {
             let public_directory_contract_address = Utils::vec_u8_to_hex_string(
                 tr.public_directory.contract_address.as_bytes().to_vec(),
             )
             .unwrap();
-            let public_directory_chain_id = &tr.public_directory.chain_id;
+            Ok((
+                public_directory_contract_address,
+                tr.public_directory.chain_id.clone(),
+            ))
+        }
+        None => {
+            let trace_id = Uuid::new_v4();
+            let message = "Unable to get Trusted Registiries";
+            error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, message);
+            Err(Json::from(ErrorMessage {
+                message,
+                trace_id: trace_id.to_string(),
+            }))
+        }
+    }
+}
+
+/// # Return public keys
+///
+/// Keys of the exposed registry members (`source=lacchain`, the default) or of the external
+/// source (`source=external-source-1`), optionally restricted to a `country` (iso alpha3 or
+/// alpha2), to keys not expired yet (`not_expired=true`), to a key type (`kty` `EC`, `RSA` or
+/// `OKP`), to a `kid` or to a `content_hash`. `sort` orders them by `id` (default), `country`
+/// or `expiration`
+///
+/// Sends an `ETag` and, unless `not_expired=true`, a `Last-Modified` (last trust list change);
+/// conditional requests (`If-None-Match`, `If-Modified-Since`) for unchanged keys get
+/// `304 Not Modified`
+#[openapi(tag = "Public keys")]
+#[get(
+    "/get-all?<page>&<results_per_page>&<country>&<not_expired>&<kty>&<source>&<kid>&<content_hash>&<sort>"
+)]
+pub async fn get_all(
+    connection: Connection<'_, Db>,
+    page: Option<u64>,
+    results_per_page: Option<u64>,
+    country: Option<String>,
+    not_expired: Option<bool>,
+    kty: Option<KeyType>,
+    source: Option<PublicKeySource>,
+    kid: Option<String>,
+    content_hash: Option<String>,
+    sort: Option<PublicKeySort>,
+    conditions: CacheConditions,
+) -> Cacheable<Responses<Json<SuccessMessage<PublicKeyResponseDto>>, Json<ErrorMessage<'static>>>> {
+    let filter = PublicKeyFilter {
+        source: source.unwrap_or_default(),
+        // unknown codes are kept so that they match no key
+        country_code: country.map(|v| normalize_country_code(&v).unwrap_or(v)),
+        not_expired_at: match not_expired {
+            Some(true) => Some(Utc::now().timestamp()),
+            _ => None,
+        },
+        kty,
+        kid: kid.map(|v| PublicKeyService::normalize_kid(&v)),
+        content_hash: content_hash.map(|v| v.to_lowercase()),
+    };
+    Cacheable(match get_exposed_public_directory() {
+        Ok((public_directory_contract_address, public_directory_chain_id)) => {
             PublicKeyService::get_all_from_lacchain(
                 connection,
                 page,
                 results_per_page,
                 &public_directory_contract_address,
                 &public_directory_chain_id,
+                &filter,
+                sort.unwrap_or_default(),
+                &conditions,
             )
             .await
         }
-        None => {
-            let trace_id = Uuid::new_v4();
-            let message = "Unable to get Trusted Registiries";
-            error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, message);
-            return Responses::BadRequest(Json::from(ErrorMessage {
-                message,
-                trace_id: trace_id.to_string(),
-            }));
+        Err(e) => Responses::BadRequest(e),
+    })
+}
+
+/// # Return the trusted public keys as a JWK Set
+///
+/// Every trusted key of the exposed registry, unpaginated, with its `kid`, `x5c`, `x5t#S256`
+/// and the `country` (iso alpha3) it is registered for. Also served at `/.well-known/jwks.json`.
+/// Supports conditional requests like `/public-key/get-all`
+#[openapi(tag = "Public keys")]
+#[get("/jwks.json")]
+pub async fn get_jwks(
+    connection: Connection<'_, Db>,
+    conditions: CacheConditions,
+) -> Cacheable<Responses<Json<JwksResponseDto>, Json<ErrorMessage<'static>>>> {
+    Cacheable(match get_exposed_public_directory() {
+        Ok((public_directory_contract_address, public_directory_chain_id)) => {
+            PublicKeyService::get_jwks_from_lacchain(
+                connection,
+                &public_directory_contract_address,
+                &public_directory_chain_id,
+                &conditions,
+            )
+            .await
+        }
+        Err(e) => Responses::BadRequest(e),
+    })
+}
+
+/// # Return the public keys registered with a kid
+///
+/// The `kid` is base64 (percent-encoded) or base64url. Every key registered with it, from the
+/// registry members and from the external source, with its provenance: source, DID and block
+/// of the LACChain registration or url of the external source, compromise and CSCA chain state
+#[openapi(tag = "Public keys")]
+#[get("/<kid>")]
+pub async fn get_by_kid(
+    connection: Connection<'_, Db>,
+    kid: String,
+) -> Responses<Json<SuccessMessage<Vec<PublicKeyDetailResponseDto>>>, Json<ErrorMessage<'static>>> {
+    match get_exposed_public_directory() {
+        Ok((public_directory_contract_address, public_directory_chain_id)) => {
+            PublicKeyService::get_by_kid_from_lacchain(
+                connection,
+                &kid,
+                &public_directory_contract_address,
+                &public_directory_chain_id,
+            )
+            .await
         }
+        Err(e) => Responses::BadRequest(e),
     }
 }
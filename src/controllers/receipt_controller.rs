@@ -0,0 +1,19 @@
+use rocket::get;
+use rocket::serde::json::Json;
+use rocket_okapi::openapi;
+
+use crate::dto::response::signing_key_response_dto::SigningKeyResponseDto;
+use crate::responses::error_message::ErrorMessage;
+use crate::responses::generic_response::Responses;
+use crate::responses::success_messages::SuccessMessage;
+use crate::services::receipt::receipt_service::get_receipt_public_key;
+
+/// # Return the public key that signs verification receipts
+///
+/// Receipts are ES256 compact JWS whose header `kid` matches the `kid` of this jwk
+#[openapi(tag = "Receipts")]
+#[get("/public-key")]
+pub async fn get_public_key(
+) -> Responses<Json<SuccessMessage<SigningKeyResponseDto>>, Json<ErrorMessage<'static>>> {
+    get_receipt_public_key()
+}
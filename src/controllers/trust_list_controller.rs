@@ -0,0 +1,82 @@
+use crate::controllers::public_key_controller::get_exposed_public_directory;
+use crate::databases::pool::Db;
+use crate::dto::cache_conditions::CacheConditions;
+use crate::dto::response::signing_key_response_dto::SigningKeyResponseDto;
+use crate::dto::response::trust_list_response_dto::{
+    SignedTrustListResponseDto, TrustListChangesResponseDto,
+};
+use crate::responses::cached_response::Cacheable;
+use crate::responses::error_message::ErrorMessage;
+use crate::responses::generic_response::Responses;
+use crate::responses::success_messages::SuccessMessage;
+use crate::services::trust_list::trust_list_service::{
+    get_signed_trust_list, get_trust_list_changes_since, get_trust_list_public_key,
+};
+use rocket::get;
+use rocket::serde::json::Json;
+use rocket_okapi::openapi;
+use sea_orm_rocket::Connection;
+
+/// # Return the signed trust list
+///
+/// Trusted keys of the exposed registry in an ES256 compact JWS holding a `version` that
+/// increases whenever the keys change, the signing time (`iat`) and an expiration (`exp`).
+/// Offline verifiers check the signature with `/trust-list/public-key`, reject expired trust
+/// lists and never replace a trust list with one of a lower version. Every response is freshly
+/// signed, so it carries no cache validators: poll `/trust-list/changes` to know when to download
+#[openapi(tag = "Trust list")]
+#[get("/")]
+pub async fn get_trust_list(
+    connection: Connection<'_, Db>,
+) -> Responses<Json<SuccessMessage<SignedTrustListResponseDto>>, Json<ErrorMessage<'static>>> {
+    match get_exposed_public_directory() {
+        Ok((public_directory_contract_address, public_directory_chain_id)) => {
+            let db = connection.into_inner();
+            get_signed_trust_list(
+                db,
+                &public_directory_contract_address,
+                &public_directory_chain_id,
+            )
+            .await
+        }
+        Err(e) => Responses::BadRequest(e),
+    }
+}
+
+/// # Return the public key that signs the trust list
+#[openapi(tag = "Trust list")]
+#[get("/public-key")]
+pub async fn get_trust_list_key(
+) -> Responses<Json<SuccessMessage<SigningKeyResponseDto>>, Json<ErrorMessage<'static>>> {
+    get_trust_list_public_key()
+}
+
+/// # Return the keys that changed since a trust list version
+///
+/// Keys `added`, `updated` and `removed` since the `since` version (zero for a full sync),
+/// along with the current `version` to send as `since` on the next sync. Supports conditional
+/// requests like `/public-key/get-all`
+#[openapi(tag = "Trust list")]
+#[get("/changes?<since>")]
+pub async fn get_trust_list_changes(
+    connection: Connection<'_, Db>,
+    since: i64,
+    conditions: CacheConditions,
+) -> Cacheable<
+    Responses<Json<SuccessMessage<TrustListChangesResponseDto>>, Json<ErrorMessage<'static>>>,
+> {
+    Cacheable(match get_exposed_public_directory() {
+        Ok((public_directory_contract_address, public_directory_chain_id)) => {
+            let db = connection.into_inner();
+            get_trust_list_changes_since(
+                db,
+                &public_directory_contract_address,
+                &public_directory_chain_id,
+                since,
+                &conditions,
+            )
+            .await
+        }
+        Err(e) => Responses::BadRequest(e),
+    })
+}
@@ -1,3 +1,8 @@
+pub mod accept;
+pub mod api_client;
+pub mod cache_conditions;
+pub mod image;
 pub mod raw;
+pub mod request;
 pub mod response;
 pub mod utils;
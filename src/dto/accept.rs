@@ -0,0 +1,33 @@
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+
+/// Whether the client sent `Accept: application/fhir+json`
+pub struct AcceptFhir(pub bool);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AcceptFhir {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let accepts_fhir = request
+            .accept()
+            .map(|accept| {
+                accept.media_types().any(|media_type| {
+                    media_type.top() == "application" && media_type.sub() == "fhir+json"
+                })
+            })
+            .unwrap_or(false);
+        Outcome::Success(AcceptFhir(accepts_fhir))
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for AcceptFhir {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
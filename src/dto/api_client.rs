@@ -0,0 +1,36 @@
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+
+use crate::services::disclosure::{api_clients::ApiClients, disclosure_profile::DisclosureProfile};
+
+/// Most disclosing profile the caller may get: the one of the api client identified by the
+/// `X-Api-Key` header, or the default one when no key is sent. Unknown keys are rejected
+pub struct AllowedDisclosure(pub DisclosureProfile);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AllowedDisclosure {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let api_clients = ApiClients::get();
+        match request.headers().get_one("X-Api-Key") {
+            Some(api_key) => match api_clients.find_by_api_key(api_key) {
+                Some(client) => Outcome::Success(AllowedDisclosure(client.disclosure)),
+                None => Outcome::Failure((Status::Unauthorized, ())),
+            },
+            None => Outcome::Success(AllowedDisclosure(api_clients.default_disclosure())),
+        }
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for AllowedDisclosure {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
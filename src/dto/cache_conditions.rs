@@ -0,0 +1,39 @@
+use chrono::DateTime;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+
+/// Conditional request headers (`If-None-Match`, `If-Modified-Since`) sent by the client
+#[derive(Clone, Debug, Default)]
+pub struct CacheConditions {
+    /// entity tags the client holds, as sent
+    pub if_none_match: Option<String>,
+    /// time (unix seconds) of the copy the client holds; ignored when not an HTTP date
+    pub if_modified_since: Option<i64>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CacheConditions {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        Outcome::Success(CacheConditions {
+            if_none_match: headers.get_one("If-None-Match").map(str::to_owned),
+            if_modified_since: headers
+                .get_one("If-Modified-Since")
+                .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+                .map(|v| v.timestamp()),
+        })
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for CacheConditions {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
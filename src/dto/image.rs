@@ -0,0 +1,62 @@
+use rocket::data::{self, Data, FromData, ToByteUnit};
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::MediaType;
+use rocket_okapi::okapi::openapi3::RequestBody;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use rocket_okapi::okapi::schemars::Map;
+use rocket_okapi::request::OpenApiFromData;
+
+use crate::dto::utils::fn_request_body;
+
+/// Raw bytes of a `image/png` or `image/jpeg` body
+#[derive(JsonSchema)]
+pub struct ImageData(pub Vec<u8>);
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for ImageData {
+    type Error = Error;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        use rocket::outcome::Outcome::*;
+        use Error::*;
+
+        // Ensure the content type is correct before opening the data.
+        match req.content_type() {
+            Some(ct) if *ct == ContentType::PNG || *ct == ContentType::JPEG => {}
+            _ => return Forward(data),
+        }
+
+        // Use a configured limit with name 'image' or fallback to default.
+        let limit = req.limits().get("image").unwrap_or(10.mebibytes());
+
+        let bytes = match data.open(limit).into_bytes().await {
+            Ok(bytes) if bytes.is_complete() => bytes.into_inner(),
+            Ok(_) => return Failure((Status::PayloadTooLarge, TooLarge)),
+            Err(e) => return Failure((Status::InternalServerError, Io(e))),
+        };
+
+        Success(ImageData(bytes))
+    }
+}
+
+impl<'r> OpenApiFromData<'r> for ImageData {
+    fn request_body(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<RequestBody> {
+        let mut request_body: rocket_okapi::Result<RequestBody> =
+            fn_request_body!(gen, ImageData, "image/png");
+        if let Ok(body) = &mut request_body {
+            if let Some(png) = body.content.get("image/png").cloned() {
+                body.content.insert("image/jpeg".to_owned(), png);
+            }
+        }
+        request_body
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    TooLarge,
+    Io(std::io::Error),
+}
@@ -0,0 +1 @@
+pub mod verify_batch_request_dto;
@@ -0,0 +1,19 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyBatchRequestDto {
+    pub items: Vec<VerifyBatchItemDto>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyBatchItemDto {
+    /// client supplied identifier, echoed back in the matching result
+    pub correlation_id: String,
+    /// base45 HC1 certificate, same content as the `/verify-b45` body
+    pub data: String,
+}
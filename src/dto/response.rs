@@ -1,2 +1,12 @@
+pub mod fhir_response_dto;
 pub mod hc1_response_dto;
+#[cfg(feature = "issuance")]
+pub mod issue_hc1_response_dto;
+pub mod policy_response_dto;
 pub mod public_key_response_dto;
+pub mod receipt_response_dto;
+pub mod shc_response_dto;
+pub mod signing_key_response_dto;
+pub mod trust_list_response_dto;
+pub mod vds_nc_response_dto;
+pub mod verify_batch_response_dto;
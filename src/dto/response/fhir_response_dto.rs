@@ -0,0 +1,187 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+// reference: https://hl7.org/fhir/R4/ and https://worldhealthorganization.github.io/ddcc/
+// only the elements filled from a verified DDCC:VS are modelled
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirBundle {
+    pub resource_type: String,
+    pub meta: FhirMeta,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<FhirIdentifier>,
+    #[serde(rename = "type")]
+    pub bundle_type: String,
+    pub timestamp: String,
+    pub entry: Vec<FhirBundleEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirBundleEntry {
+    pub full_url: String,
+    pub resource: FhirResource,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(tag = "resourceType")]
+pub enum FhirResource {
+    Composition(FhirComposition),
+    Patient(FhirPatient),
+    Immunization(FhirImmunization),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirMeta {
+    pub profile: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirIdentifier {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirCoding {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirCodeableConcept {
+    pub coding: Vec<FhirCoding>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirReference {
+    /// `fullUrl` of another entry of the bundle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<FhirIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirExtension {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_coding: Option<FhirCoding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_date_time: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirHumanName {
+    pub text: String,
+}
+
+// reference: https://worldhealthorganization.github.io/ddcc/StructureDefinition-DDCCComposition.html
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirComposition {
+    pub meta: FhirMeta,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<FhirIdentifier>,
+    pub status: String,
+    #[serde(rename = "type")]
+    pub composition_type: FhirCodeableConcept,
+    pub subject: FhirReference,
+    pub date: String,
+    pub author: Vec<FhirReference>,
+    pub title: String,
+    pub section: Vec<FhirCompositionSection>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirCompositionSection {
+    pub code: FhirCodeableConcept,
+    pub focus: FhirReference,
+    pub entry: Vec<FhirReference>,
+}
+
+// reference: https://worldhealthorganization.github.io/ddcc/StructureDefinition-DDCCPatient.html
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirPatient {
+    pub meta: FhirMeta,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub identifier: Vec<FhirIdentifier>,
+    pub name: Vec<FhirHumanName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gender: Option<String>,
+}
+
+// reference: https://worldhealthorganization.github.io/ddcc/StructureDefinition-DDCCImmunization.html
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirImmunization {
+    pub meta: FhirMeta,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extension: Vec<FhirExtension>,
+    pub status: String,
+    pub vaccine_code: FhirCodeableConcept,
+    pub patient: FhirReference,
+    pub occurrence_date_time: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<FhirReference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<FhirReference>,
+    pub lot_number: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub performer: Vec<FhirImmunizationPerformer>,
+    pub protocol_applied: Vec<FhirImmunizationProtocolApplied>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirImmunizationPerformer {
+    pub actor: FhirReference,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct FhirImmunizationProtocolApplied {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub target_disease: Vec<FhirCodeableConcept>,
+    pub dose_number_positive_int: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_doses_positive_int: Option<u8>,
+}
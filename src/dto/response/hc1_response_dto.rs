@@ -1,94 +1,54 @@
 use rocket::serde::{Deserialize, Serialize};
 use rocket_okapi::okapi::schemars::{self, JsonSchema};
 
+pub use lacpass_verifier::types::{
+    eu_dcc::EuDigitalCovidCertificate,
+    hc1::{
+        Certificate, CheckCode, CheckStatus, CheckType, CodeSystem, DdccCoreDataSet,
+        Hc1Verification, Identifier, PayloadType, Period, Recovery, TestResult, Vaccination, Value,
+        VerificationCheck,
+    },
+};
+
+use super::policy_response_dto::PolicyEvaluationDto;
+
 #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 #[serde(rename_all = "camelCase")]
 pub struct HC1ValidationResponseDto {
     pub is_valid: bool,
-    pub ddcc_core_data_set: DdccCoreDataSet,
-}
-
-#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
-#[serde(crate = "rocket::serde")]
-#[serde(rename_all = "camelCase")]
-pub struct DdccCoreDataSet {
-    pub vaccination: Vaccination,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub resource_type: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub birth_date: Option<String>,
-    pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub identifier: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sex: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub certificate: Option<Certificate>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct Vaccination {
-    pub date: String,
-    pub dose: u8,
-    pub vaccine: CodeSystem,
-    pub country: CodeSystem,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub maholder: Option<CodeSystem>,
-    pub lot: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub centre: Option<String>,
-    pub brand: CodeSystem,
+    /// key identifier (base64) of the key that verified the signature
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub manufacturer: Option<CodeSystem>,
+    pub kid: Option<String>,
+    /// outcome of every check performed; `is_valid` is true only if none of them failed
+    pub checks: Vec<VerificationCheck>,
+    /// tells which of `ddccCoreDataSet` or `euDcc` carries the certificate content
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub valid_from: Option<String>,
+    pub payload_type: Option<PayloadType>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub total_doses: Option<u8>,
+    pub ddcc_core_data_set: Option<DdccCoreDataSet>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub practitioner: Option<Value>,
+    pub eu_dcc: Option<EuDigitalCovidCertificate>,
+    /// outcome of the business rules of the policy passed in the `policy` query parameter
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub disease: Option<CodeSystem>,
+    pub policy: Option<PolicyEvaluationDto>,
+    /// compact JWS (ES256) receipt of the verification, only when requested with `receipt=true`;
+    /// its public key is published at `/receipts/public-key`
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub next_dose: Option<String>,
+    pub receipt: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct Certificate {
-    pub hcid: Value,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub period: Option<Period>,
-    pub issuer: Identifier,
-    pub version: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct CodeSystem {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub system: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct Identifier {
-    pub identifier: Value,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-pub struct Value {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub value: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-pub struct Period {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub start: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub end: Option<String>,
+impl From<Hc1Verification> for HC1ValidationResponseDto {
+    fn from(verification: Hc1Verification) -> Self {
+        HC1ValidationResponseDto {
+            is_valid: verification.is_valid,
+            kid: verification.kid,
+            checks: verification.checks,
+            payload_type: verification.payload_type,
+            ddcc_core_data_set: verification.ddcc_core_data_set,
+            eu_dcc: verification.eu_dcc,
+            policy: None,
+            receipt: None,
+        }
+    }
 }
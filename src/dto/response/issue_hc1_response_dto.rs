@@ -0,0 +1,14 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct IssueHc1ResponseDto {
+    /// "HC1:" prefixed base45 content, ready to be rendered as a QR code
+    pub data: String,
+    /// base64 encoded key identifier set in the COSE protected header
+    pub kid: String,
+    pub iat: i64,
+    pub exp: i64,
+}
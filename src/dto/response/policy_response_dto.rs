@@ -0,0 +1,28 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::hc1_response_dto::CheckStatus;
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyEvaluationDto {
+    /// name of the evaluated policy
+    pub policy: String,
+    /// true only if the certificate is valid, carries a DDCC core data set and none of the
+    /// rules failed
+    pub passed: bool,
+    pub rules: Vec<RuleResultDto>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct RuleResultDto {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
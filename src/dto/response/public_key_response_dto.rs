@@ -1,6 +1,10 @@
 use rocket::serde::{Deserialize, Serialize};
 use rocket_okapi::okapi::schemars::{self, JsonSchema};
 
+pub use lacpass_verifier::types::jwk::Jwk;
+
+use crate::entities::public_key::filter::PublicKeySource;
+
 #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct PublicKeyResponseDto {
@@ -19,29 +23,61 @@ pub struct PublicKeyCoreResponse {
     pub jwk: Jwk,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct Jwk {
+/// JWK Set (reference: https://www.rfc-editor.org/rfc/rfc7517#section-5)
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct JwksResponseDto {
+    pub keys: Vec<JwksKey>,
+}
+
+/// Trusted key of the JWK Set; `kid` is the DCC key identifier (base64 of the first 8 bytes of
+/// the certificate SHA-256)
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct JwksKey {
+    #[serde(flatten)]
+    pub jwk: Jwk,
+    /// base64url encoded SHA-256 of the DER certificate
+    #[serde(rename = "x5t#S256")]
+    pub x5t_s256: String,
+    /// extension member: iso alpha3 code of the country the key is registered for
+    pub country: String,
+}
+
+/// Where a public key comes from and its current state
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PublicKeyProvenance {
+    pub source: PublicKeySource,
+    /// DID that registered the key in the public directory (`lacchain` source)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub alg: Option<String>,
+    pub did: Option<String>,
+    /// block of the last DID registry event that changed the key (`lacchain` source)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub r#use: Option<String>,
-    pub kty: Option<String>,
+    pub block_number: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub kid: Option<String>,
-    pub x5c: Option<Vec<String>>,
+    pub url: Option<String>,
+    /// hex keccak-256 of the DER certificate
+    pub content_hash: String,
+    /// time (unix seconds) the key certificate expires
+    pub exp: Option<i64>,
+    pub is_compromised: bool,
+    /// time (unix seconds) since when the key is considered compromised
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub x5t: Option<String>,
-
+    pub compromised_at: Option<i64>,
+    /// whether the key certificate chains to a trusted CSCA of its country; absent when no CSCA
+    /// is configured for the country
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub n: Option<String>,
+    pub is_chain_trusted: Option<bool>,
+    /// comma separated certificate types (`t`, `v`, `r`) the key is restricted to; absent when it
+    /// may sign any type
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub e: Option<String>,
+    pub certificate_types: Option<String>,
+}
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub x: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub y: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub crv: Option<String>,
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PublicKeyDetailResponseDto {
+    pub key: JwksKey,
+    pub provenance: PublicKeyProvenance,
 }
@@ -0,0 +1,26 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::hc1_response_dto::VerificationCheck;
+
+/// Claims of a verification receipt (payload of the receipt JWS); no certificate content is
+/// kept, only a hash of what was submitted
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReceiptClaims {
+    /// trace id of the verification request, followed by `.<index>` for the items of a batch
+    pub jti: String,
+    /// time (unix seconds) of the verification
+    pub iat: i64,
+    /// hex encoded sha-256 of the request body (the image bytes for `/verify-image`, the item
+    /// `data` for `/verify-b45/batch`)
+    pub input_sha256: String,
+    pub is_valid: bool,
+    pub checks: Vec<VerificationCheck>,
+    /// key identifier of the key that verified the signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// trust list version (as in `/trust-list/changes`) the certificate was checked against
+    pub trust_list_version: i64,
+}
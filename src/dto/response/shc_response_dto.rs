@@ -0,0 +1,44 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::hc1_response_dto::{CodeSystem, VerificationCheck};
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct ShcValidationResponseDto {
+    pub is_valid: bool,
+    /// JWK thumbprint of the key that verified the signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// iso alpha3 country code the verifying key is registered for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// `iss` claim, the url of the issuer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// outcome of every check performed; `is_valid` is true only if none of them failed
+    pub checks: Vec<VerificationCheck>,
+    /// FHIR Immunization entries found in the card bundle
+    pub immunizations: Vec<ShcImmunization>,
+    /// compact JWS (ES256) receipt of the verification, only when requested with `receipt=true`;
+    /// its public key is published at `/receipts/public-key`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<String>,
+}
+
+// reference: https://hl7.org/fhir/R4/immunization.html
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct ShcImmunization {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    pub vaccine_code: Vec<CodeSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occurrence_date_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lot_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub performer: Option<String>,
+}
@@ -0,0 +1,17 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::public_key_response_dto::Jwk;
+
+/// Public key of one of the keys the service signs its own documents with
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct SigningKeyResponseDto {
+    /// JWS algorithm of the signed documents
+    pub alg: String,
+    /// public key as a jwk, its `kid` is the one found in the JWS header
+    pub jwk: Jwk,
+    /// public key as a pem encoded SubjectPublicKeyInfo
+    pub pem: String,
+}
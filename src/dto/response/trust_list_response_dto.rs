@@ -0,0 +1,49 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::public_key_response_dto::JwksKey;
+
+/// Claims of the signed trust list (payload of its JWS)
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct TrustListClaims {
+    /// increases every time the keys change; clients must refuse a version lower than the last
+    /// one they accepted
+    pub version: i64,
+    /// time (unix seconds) the trust list was signed
+    pub iat: i64,
+    /// time (unix seconds) after which the trust list is stale and must be downloaded again
+    pub exp: i64,
+    /// trusted keys, same members as the JWK Set at `/.well-known/jwks.json`
+    pub keys: Vec<JwksKey>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTrustListResponseDto {
+    /// same as the `version` claim, to skip downloads of a known version
+    pub version: i64,
+    /// compact JWS (ES256) whose payload are the trust list claims; only its verified content
+    /// is to be trusted. The signing key is published at `/trust-list/public-key`
+    pub trust_list: String,
+}
+
+/// Keys that changed in the trust list between two versions
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct TrustListChangesResponseDto {
+    /// version the changes are computed from, as sent by the client
+    pub since: i64,
+    /// current version; send it as `since` on the next sync
+    pub version: i64,
+    /// keys that became trusted after `since`
+    pub added: Vec<JwksKey>,
+    /// trusted keys whose registration changed after `since` (e.g. their expiration)
+    pub updated: Vec<JwksKey>,
+    /// keys that stopped being trusted after `since` (compromised or not chaining to a trusted
+    /// CSCA); match them by `kid` or `x5t#S256`
+    pub removed: Vec<JwksKey>,
+}
@@ -0,0 +1,32 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::hc1_response_dto::{Vaccination, VerificationCheck};
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VdsNcValidationResponseDto {
+    pub is_valid: bool,
+    /// key identifier (base64) of the embedded signer certificate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// iso alpha3 country code of the issuing state (`data.hdr.is`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// outcome of every check performed; `is_valid` is true only if none of them failed
+    pub checks: Vec<VerificationCheck>,
+    /// unique vaccination certificate identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uvci: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<String>,
+    /// one entry per vaccination event
+    pub vaccinations: Vec<Vaccination>,
+    /// compact JWS (ES256) receipt of the verification, only when requested with `receipt=true`;
+    /// its public key is published at `/receipts/public-key`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<String>,
+}
@@ -0,0 +1,24 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use super::hc1_response_dto::HC1ValidationResponseDto;
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyBatchResponseDto {
+    /// one result per item, in the same order as the request
+    pub results: Vec<VerifyBatchItemResultDto>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyBatchItemResultDto {
+    pub correlation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<HC1ValidationResponseDto>,
+    /// set when the item could not be verified because of an internal error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -3,5 +3,8 @@ pub mod entities;
 pub mod models;
 pub mod pd_did_member;
 pub mod pd_member;
+pub mod policy;
 pub mod public_directory;
 pub mod public_key;
+pub mod public_key_change;
+pub mod trust_list_version;
@@ -1,5 +1,8 @@
 pub use crate::entities::did::model::Entity as DidEntity;
 pub use crate::entities::pd_did_member::model::Entity as PdDidMemberEntity;
 pub use crate::entities::pd_member::model::Entity as PdMemberEntity;
+pub use crate::entities::policy::model::Entity as PolicyEntity;
 pub use crate::entities::public_directory::model::Entity as PublicDirectoryEntity;
 pub use crate::entities::public_key::model::Entity as PublicKeyEntity;
+pub use crate::entities::public_key_change::model::Entity as PublicKeyChangeEntity;
+pub use crate::entities::trust_list_version::model::Entity as TrustListVersionEntity;
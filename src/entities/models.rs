@@ -12,3 +12,12 @@ pub use crate::entities::pd_did_member::model::Model as PdDidMemberModel;
 
 pub use crate::entities::public_key::model::ActiveModel as PublicKeyActiveModel;
 pub use crate::entities::public_key::model::Model as PublicKeyModel;
+
+pub use crate::entities::policy::model::ActiveModel as PolicyActiveModel;
+pub use crate::entities::policy::model::Model as PolicyModel;
+
+pub use crate::entities::trust_list_version::model::ActiveModel as TrustListVersionActiveModel;
+pub use crate::entities::trust_list_version::model::Model as TrustListVersionModel;
+
+pub use crate::entities::public_key_change::model::ActiveModel as PublicKeyChangeActiveModel;
+pub use crate::entities::public_key_change::model::Model as PublicKeyChangeModel;
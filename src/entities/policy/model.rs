@@ -0,0 +1,23 @@
+use rocket::serde::{Deserialize, Serialize};
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+#[sea_orm(table_name = "policy")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// value expected in the `policy` query parameter
+    pub name: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    /// JSON array of rules
+    #[sea_orm(column_type = "Text")]
+    pub rules: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,14 @@
+use sea_orm::ColumnTrait;
+use sea_orm::EntityTrait;
+use sea_orm::QueryFilter;
+use sea_orm::Select;
+
+use crate::entities::entities::PolicyEntity;
+
+use super::model;
+
+impl PolicyEntity {
+    pub fn find_by_name(name: &str) -> Select<Self> {
+        Self::find().filter(model::Column::Name.eq(name))
+    }
+}
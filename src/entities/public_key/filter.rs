@@ -0,0 +1,73 @@
+use rocket::serde::{Deserialize, Serialize};
+use rocket::FromFormField;
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+/// Where a public key was collected from
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Serialize, JsonSchema, FromFormField, PartialEq, Eq,
+)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "kebab-case")]
+pub enum PublicKeySource {
+    /// DID of a member of the LACChain public directory
+    #[default]
+    #[field(value = "lacchain")]
+    Lacchain,
+    /// keys downloaded from `EXTERNAL_SOURCE_1`
+    #[field(value = "external-source-1")]
+    ExternalSource1,
+}
+
+/// JWK key type (`kty`)
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, FromFormField, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub enum KeyType {
+    #[serde(rename = "EC")]
+    #[field(value = "EC")]
+    Ec,
+    #[serde(rename = "RSA")]
+    #[field(value = "RSA")]
+    Rsa,
+    #[serde(rename = "OKP")]
+    #[field(value = "OKP")]
+    Okp,
+}
+
+impl KeyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyType::Ec => "EC",
+            KeyType::Rsa => "RSA",
+            KeyType::Okp => "OKP",
+        }
+    }
+}
+
+/// Order of the listed public keys; ties are broken by id so pages are stable
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, JsonSchema, FromFormField)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "kebab-case")]
+pub enum PublicKeySort {
+    #[default]
+    #[field(value = "id")]
+    Id,
+    /// iso alpha3 country code
+    #[field(value = "country")]
+    Country,
+    /// expiration, the soonest first
+    #[field(value = "expiration")]
+    Expiration,
+}
+
+/// Restricts the public keys returned by `find_by_public_directory`; unset fields match any key
+#[derive(Clone, Debug, Default)]
+pub struct PublicKeyFilter {
+    pub source: PublicKeySource,
+    /// iso alpha3 country code
+    pub country_code: Option<String>,
+    /// only keys not expired at this time (unix seconds)
+    pub not_expired_at: Option<i64>,
+    pub kty: Option<KeyType>,
+    pub kid: Option<String>,
+    pub content_hash: Option<String>,
+}
@@ -14,9 +14,20 @@ pub struct Model {
     pub jwk: Vec<u8>,
     pub exp: Option<i64>,
     pub is_compromised: Option<bool>,
+    /// time (unix seconds) since when the key is considered compromised
+    pub compromised_at: Option<i64>,
     pub did_id: Option<Uuid>,
     pub block_number: Option<i64>,
     pub url: Option<String>,
+    pub kid: Option<String>,
+    /// whether the key certificate chains to a trusted CSCA of its country; `None` when no CSCA
+    /// is configured for the country
+    pub is_chain_trusted: Option<bool>,
+    /// comma separated certificate types (`t`, `v`, `r`) the key is restricted to by its extended
+    /// key usage; `None` when it may sign any type
+    pub certificate_types: Option<String>,
+    /// JWK thumbprint (RFC 7638) of the key, which SMART Health Cards use as `kid`
+    pub jwk_thumbprint: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
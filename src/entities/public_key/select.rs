@@ -7,6 +7,7 @@ use sea_orm::EntityTrait;
 use sea_orm::FromQueryResult;
 use sea_orm::JoinType;
 use sea_orm::QueryFilter;
+use sea_orm::QueryOrder;
 use sea_orm::QuerySelect;
 use sea_orm::QueryTrait;
 use sea_orm::RelationTrait;
@@ -23,6 +24,7 @@ use crate::entities::entities::PublicDirectoryEntity;
 use crate::entities::pd_member;
 use crate::entities::public_directory::model::Column as Pd;
 
+use super::filter::{PublicKeyFilter, PublicKeySort, PublicKeySource};
 use super::model;
 
 #[derive(Debug, FromQueryResult)]
@@ -46,7 +48,43 @@ impl PublicKeyEntity {
         )
     }
 
-    pub fn find_by_country_code(country_code: &str) -> Select<Self> {
+    /// Keys usable to verify certificates issued now: not flagged as compromised and not rejected
+    /// by the CSCA trust store. Verifying a given certificate relies on `TrustedKey::is_usable_at`
+    /// instead, which also accepts keys compromised after the certificate was issued
+    fn usable_now() -> Condition {
+        let not_compromised = Condition::any()
+            .add(model::Column::IsCompromised.is_null())
+            .add(model::Column::IsCompromised.eq(false));
+        let chain_not_rejected = Condition::any()
+            .add(model::Column::IsChainTrusted.is_null())
+            .add(model::Column::IsChainTrusted.eq(true));
+        Condition::all()
+            .add(chain_not_rejected)
+            .add(not_compromised)
+    }
+
+    /// Keys of the country registered under `kid`, along with the keys whose kid is not stored
+    /// yet; compromised ones included
+    pub fn find_by_country_code_and_kid(country_code: &str, kid: &str) -> Select<Self> {
+        Self::find().filter(
+            Condition::all()
+                .add(model::Column::CountryCode.eq(country_code))
+                .add(
+                    Condition::any()
+                        .add(model::Column::Kid.eq(kid))
+                        .add(model::Column::Kid.is_null()),
+                ),
+        )
+    }
+
+    /// Keys across every country whose JWK thumbprint is `jwk_thumbprint`, compromised ones
+    /// included
+    pub fn find_by_jwk_thumbprint(jwk_thumbprint: &str) -> Select<Self> {
+        Self::find().filter(model::Column::JwkThumbprint.eq(jwk_thumbprint))
+    }
+
+    /// Every key of the country, compromised ones included
+    pub fn find_all_by_country_code(country_code: &str) -> Select<Self> {
         Self::find().filter(model::Column::CountryCode.eq(country_code))
     }
 
@@ -58,12 +96,73 @@ impl PublicKeyEntity {
         )
     }
 
+    /// Every key, compromised ones included, in a stable order
+    pub fn find_all_ordered() -> Select<Self> {
+        Self::find().order_by_asc(model::Column::Id)
+    }
+
     pub fn find_by_id(id: &Uuid) -> Select<Self> {
         Self::find().filter(model::Column::Id.eq(*id))
     }
+    /// Conditions of the `filter` other than the source
+    fn matching(filter: &PublicKeyFilter) -> Condition {
+        let mut condition = Condition::all();
+        if let Some(country_code) = &filter.country_code {
+            condition = condition.add(model::Column::CountryCode.eq(country_code.as_str()));
+        }
+        if let Some(now) = filter.not_expired_at {
+            condition = condition.add(
+                Condition::any()
+                    .add(model::Column::Exp.is_null())
+                    .add(model::Column::Exp.gt(now)),
+            );
+        }
+        if let Some(kty) = filter.kty {
+            // the jwk is stored as json bytes
+            condition = condition.add(Expr::cust_with_values(
+                r#"convert_from("public_key"."jwk", 'UTF8')::jsonb ->> 'kty' = $1"#,
+                [kty.as_str()],
+            ));
+        }
+        if let Some(kid) = &filter.kid {
+            condition = condition.add(model::Column::Kid.eq(kid.as_str()));
+        }
+        if let Some(content_hash) = &filter.content_hash {
+            condition = condition.add(model::Column::ContentHash.eq(content_hash.as_str()));
+        }
+        condition
+    }
+
+    /// Keys of the public directory members (LACChain source) or of the external source,
+    /// restricted by the `filter`
     pub fn find_by_public_directory(
         public_directory_contract_address: &str,
         chain_id: &str,
+        filter: &PublicKeyFilter,
+    ) -> Select<Self> {
+        let select = match filter.source {
+            PublicKeySource::Lacchain => {
+                Self::find_by_public_directory_members(public_directory_contract_address, chain_id)
+            }
+            // external sources are not registered through DIDs
+            PublicKeySource::ExternalSource1 => Self::find().filter(model::Column::DidId.is_null()),
+        };
+        select.filter(Self::matching(filter))
+    }
+
+    /// Same select ordered by `sort`, then by id
+    pub fn sorted(select: Select<Self>, sort: PublicKeySort) -> Select<Self> {
+        let select = match sort {
+            PublicKeySort::Id => select,
+            PublicKeySort::Country => select.order_by_asc(model::Column::CountryCode),
+            PublicKeySort::Expiration => select.order_by_asc(model::Column::Exp),
+        };
+        select.order_by_asc(model::Column::Id)
+    }
+
+    fn find_by_public_directory_members(
+        public_directory_contract_address: &str,
+        chain_id: &str,
     ) -> Select<Self> {
         let public_directory_contract_address = public_directory_contract_address.to_owned();
         let chain_id = chain_id.to_owned();
@@ -119,11 +218,49 @@ impl PublicKeyEntity {
             );
         select
     }
+    /// Keys of the public directory that are usable to verify certificates issued now: not
+    /// compromised and not rejected by the CSCA trust store
+    pub fn find_trusted_by_public_directory(
+        public_directory_contract_address: &str,
+        chain_id: &str,
+    ) -> Select<Self> {
+        Self::find_by_public_directory(
+            public_directory_contract_address,
+            chain_id,
+            &PublicKeyFilter::default(),
+        )
+        .filter(Self::usable_now())
+        .order_by_asc(model::Column::Id)
+    }
+    /// Keys of the public directory among `ids`, whatever their state, ordered by id
+    pub fn find_by_public_directory_and_ids(
+        public_directory_contract_address: &str,
+        chain_id: &str,
+        ids: &[Uuid],
+    ) -> Select<Self> {
+        Self::find_by_public_directory(
+            public_directory_contract_address,
+            chain_id,
+            &PublicKeyFilter::default(),
+        )
+        .filter(model::Column::Id.is_in(ids.to_vec()))
+        .order_by_asc(model::Column::Id)
+    }
+    /// Same as `find_trusted_by_public_directory` restricted to `ids`
+    pub fn find_trusted_by_public_directory_and_ids(
+        public_directory_contract_address: &str,
+        chain_id: &str,
+        ids: &[Uuid],
+    ) -> Select<Self> {
+        Self::find_trusted_by_public_directory(public_directory_contract_address, chain_id)
+            .filter(model::Column::Id.is_in(ids.to_vec()))
+    }
     pub fn find_with_country(
         public_directory_contract_address: &str,
         chain_id: &str,
     ) -> SelectorRaw<SelectModel<PublicKeyMemberView>> {
-        let pd_select = Self::find_by_public_directory(public_directory_contract_address, chain_id);
+        let pd_select =
+            Self::find_by_public_directory_members(public_directory_contract_address, chain_id);
         PublicKeyMemberView::find_by_statement(pd_select.build(DbBackend::Postgres))
     }
 }
@@ -0,0 +1,23 @@
+use rocket::serde::{Deserialize, Serialize};
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+#[sea_orm(table_name = "public_key_change")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// trust list version created by this change
+    pub version: i64,
+    pub public_key_id: Uuid,
+    /// "added" or "updated"
+    pub change_type: String,
+    /// time (unix seconds) the change was recorded
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
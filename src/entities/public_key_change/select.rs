@@ -0,0 +1,18 @@
+use sea_orm::ColumnTrait;
+use sea_orm::EntityTrait;
+use sea_orm::QueryFilter;
+use sea_orm::QueryOrder;
+use sea_orm::Select;
+
+use crate::entities::entities::PublicKeyChangeEntity;
+
+use super::model;
+
+impl PublicKeyChangeEntity {
+    /// Changes recorded after `version`, oldest first
+    pub fn find_since(version: i64) -> Select<Self> {
+        Self::find()
+            .filter(model::Column::Version.gt(version))
+            .order_by_asc(model::Column::Version)
+    }
+}
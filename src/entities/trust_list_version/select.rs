@@ -0,0 +1,14 @@
+use sea_orm::EntityTrait;
+use sea_orm::QueryOrder;
+use sea_orm::Select;
+
+use crate::entities::entities::TrustListVersionEntity;
+
+use super::model;
+
+impl TrustListVersionEntity {
+    /// Versions from the newest, `.one()` gives the current one
+    pub fn find_latest() -> Select<Self> {
+        Self::find().order_by_desc(model::Column::Version)
+    }
+}
@@ -4,3 +4,12 @@ pub mod m20230622_011005_did;
 pub mod m20230622_035815_pd_member;
 pub mod m20230622_044839_pd_did_member;
 pub mod m20230623_215702_public_key;
+pub mod m20231020_140512_public_key_kid;
+pub mod m20231021_093027_public_key_compromised_at;
+pub mod m20231101_100000_policy;
+pub mod m20231102_100000_public_key_is_chain_trusted;
+pub mod m20231103_100000_public_key_certificate_types;
+pub mod m20231104_100000_trust_list_version;
+pub mod m20231105_100000_public_key_change;
+pub mod m20231106_100000_public_key_change_seed;
+pub mod m20231107_100000_public_key_jwk_thumbprint;
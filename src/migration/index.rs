@@ -7,6 +7,11 @@ use crate::{
     migration::{
         m20230617_195505_public_directory, m20230622_011005_did, m20230622_035815_pd_member,
         m20230622_044839_pd_did_member, m20230623_215702_public_key,
+        m20231020_140512_public_key_kid, m20231021_093027_public_key_compromised_at,
+        m20231101_100000_policy, m20231102_100000_public_key_is_chain_trusted,
+        m20231103_100000_public_key_certificate_types, m20231104_100000_trust_list_version,
+        m20231105_100000_public_key_change, m20231106_100000_public_key_change_seed,
+        m20231107_100000_public_key_jwk_thumbprint,
     },
 };
 pub struct Migrator;
@@ -20,6 +25,15 @@ impl MigratorTrait for Migrator {
             Box::new(m20230622_035815_pd_member::Migration),
             Box::new(m20230622_044839_pd_did_member::Migration),
             Box::new(m20230623_215702_public_key::Migration),
+            Box::new(m20231020_140512_public_key_kid::Migration),
+            Box::new(m20231021_093027_public_key_compromised_at::Migration),
+            Box::new(m20231101_100000_policy::Migration),
+            Box::new(m20231102_100000_public_key_is_chain_trusted::Migration),
+            Box::new(m20231103_100000_public_key_certificate_types::Migration),
+            Box::new(m20231104_100000_trust_list_version::Migration),
+            Box::new(m20231105_100000_public_key_change::Migration),
+            Box::new(m20231106_100000_public_key_change_seed::Migration),
+            Box::new(m20231107_100000_public_key_jwk_thumbprint::Migration),
         ]
     }
 }
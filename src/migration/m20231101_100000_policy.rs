@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Policy::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Policy::Id).uuid().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(Policy::Name)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Policy::Description).text().null())
+                    .col(ColumnDef::new(Policy::Rules).text().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Policy::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub(crate) enum Policy {
+    Table,
+    Id,
+    Name,
+    Description,
+    Rules,
+}
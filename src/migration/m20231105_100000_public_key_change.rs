@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PublicKeyChange::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PublicKeyChange::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PublicKeyChange::Version)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PublicKeyChange::PublicKeyId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PublicKeyChange::ChangeType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PublicKeyChange::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("public_key_change_version")
+                    .table(PublicKeyChange::Table)
+                    .col(PublicKeyChange::Version)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PublicKeyChange::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum PublicKeyChange {
+    Table,
+    Id,
+    Version,
+    PublicKeyId,
+    ChangeType,
+    CreatedAt,
+}
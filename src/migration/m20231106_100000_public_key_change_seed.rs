@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Keys stored before changes were recorded get an `added` change at a new version, so that
+/// clients synced to an earlier version receive them in the change feed
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "INSERT INTO trust_list_version (id, version, created_at)
+            SELECT gen_random_uuid(), COALESCE(MAX(version), 0) + 1,
+                EXTRACT(EPOCH FROM NOW())::BIGINT
+            FROM trust_list_version
+            WHERE EXISTS (
+                SELECT 1 FROM public_key
+                WHERE id NOT IN (SELECT public_key_id FROM public_key_change)
+            )",
+        )
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO public_key_change (id, version, public_key_id, change_type, created_at)
+            SELECT gen_random_uuid(), (SELECT MAX(version) FROM trust_list_version), id, 'added',
+                EXTRACT(EPOCH FROM NOW())::BIGINT
+            FROM public_key
+            WHERE id NOT IN (SELECT public_key_id FROM public_key_change)",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use lacpass_verifier::x509::X509Utils;
+use sea_orm::Statement;
+use sea_orm_migration::prelude::*;
+use uuid::Uuid;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Thumbprints of the keys already stored are computed here, lookups by thumbprint never fall
+/// back to keys without one
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PublicKey::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(PublicKey::JwkThumbprint).string().null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("jwk_thumbprint")
+                    .table(PublicKey::Table)
+                    .col(PublicKey::JwkThumbprint)
+                    .to_owned(),
+            )
+            .await?;
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let rows = db
+            .query_all(Statement::from_string(
+                backend,
+                "SELECT id, jwk FROM public_key WHERE jwk_thumbprint IS NULL".to_owned(),
+            ))
+            .await?;
+        for row in rows {
+            let id: Uuid = row.try_get("", "id")?;
+            let jwk: Vec<u8> = row.try_get("", "jwk")?;
+            // keys whose jwk can not be read can not verify anything either
+            let jwk_thumbprint = match String::from_utf8(jwk)
+                .ok()
+                .and_then(|jwk| X509Utils::get_pem_from_string_jwk(&jwk).ok())
+                .and_then(|pem| X509Utils::get_jwk_thumbprint_from_pem(pem).ok())
+            {
+                Some(v) => v,
+                None => continue,
+            };
+            db.execute(Statement::from_sql_and_values(
+                backend,
+                "UPDATE public_key SET jwk_thumbprint = $1 WHERE id = $2",
+                [jwk_thumbprint.into(), id.into()],
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("jwk_thumbprint")
+                    .table(PublicKey::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PublicKey::Table)
+                    .drop_column(PublicKey::JwkThumbprint)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum PublicKey {
+    Table,
+    JwkThumbprint,
+}
@@ -1,3 +1,5 @@
+pub mod cached_response;
 pub mod error_message;
 pub mod generic_response;
+pub mod negotiated_response;
 pub mod success_messages;
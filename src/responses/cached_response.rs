@@ -0,0 +1,154 @@
+use chrono::{TimeZone, Utc};
+use openssl::sha::sha256;
+use rocket::http::Header;
+use rocket::response::{self, Responder};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::{RefOr, Response as OpenApiReponse, Responses};
+use rocket_okapi::response::OpenApiResponderInner;
+use serde::Serialize;
+
+use crate::dto::cache_conditions::CacheConditions;
+
+/// Validators a client can send back to only download content that changed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheValidators {
+    /// opaque tag of the content, sent as a weak `ETag` since bodies hold a per request trace id
+    pub tag: String,
+    /// time (unix seconds) the content last changed, when known
+    pub last_modified: Option<i64>,
+}
+
+impl CacheValidators {
+    /// Tags the content by the hex sha-256 of its json serialization
+    pub fn from_content<S: Serialize>(
+        content: &S,
+        last_modified: Option<i64>,
+    ) -> anyhow::Result<Self> {
+        Ok(CacheValidators {
+            tag: hex::encode(sha256(&serde_json::to_vec(content)?)),
+            last_modified,
+        })
+    }
+
+    pub fn get_etag(&self) -> String {
+        format!("W/\"{}\"", self.tag)
+    }
+
+    /// `Last-Modified` value (HTTP date)
+    pub fn get_last_modified(&self) -> Option<String> {
+        self.last_modified
+            .and_then(|v| Utc.timestamp_opt(v, 0).single())
+            .map(|v| v.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+    }
+
+    /// Whether the client already holds this content. `If-None-Match` is compared weakly and,
+    /// when sent, takes precedence over `If-Modified-Since`
+    pub fn is_fresh(&self, conditions: &CacheConditions) -> bool {
+        match &conditions.if_none_match {
+            Some(tags) => tags.split(',').map(str::trim).any(|tag| {
+                tag == "*" || tag.trim_start_matches("W/") == format!("\"{}\"", self.tag)
+            }),
+            None => match (conditions.if_modified_since, self.last_modified) {
+                (Some(since), Some(last_modified)) => last_modified <= since,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Adds the `ETag` and `Last-Modified` headers of the validators to the wrapped response
+pub struct WithValidators<R>(pub R, pub CacheValidators);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for WithValidators<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.0.respond_to(request)?;
+        response.set_header(Header::new("ETag", self.1.get_etag()));
+        if let Some(last_modified) = self.1.get_last_modified() {
+            response.set_header(Header::new("Last-Modified", last_modified));
+        }
+        Ok(response)
+    }
+}
+
+/// Response of an endpoint answering conditional requests (see `Responses::cached`); it only
+/// adds the `304 Not Modified` to the documented responses
+pub struct Cacheable<R>(pub R);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Cacheable<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        self.0.respond_to(request)
+    }
+}
+
+impl<R: OpenApiResponderInner> OpenApiResponderInner for Cacheable<R> {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let mut responses = R::responses(gen)?;
+        responses.responses.insert(
+            "304".to_string(),
+            RefOr::Object(OpenApiReponse {
+                description: "\
+                # [304 Not Modified](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/304)\n\
+                The content matches the `If-None-Match` or `If-Modified-Since` sent, it is not returned again. \
+                "
+                .to_string(),
+                ..Default::default()
+            }),
+        );
+        Ok(responses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators() -> CacheValidators {
+        CacheValidators::from_content(&vec!["a2lk"], Some(1700000000)).unwrap()
+    }
+
+    #[test]
+    fn headers_test() {
+        let validators = validators();
+        assert_eq!(
+            validators,
+            CacheValidators::from_content(&vec!["a2lk"], Some(1700000000)).unwrap()
+        );
+        assert_ne!(
+            validators.tag,
+            CacheValidators::from_content(&vec!["b2lk"], Some(1700000000))
+                .unwrap()
+                .tag
+        );
+        assert_eq!(validators.get_etag(), format!("W/\"{}\"", validators.tag));
+        assert_eq!(
+            validators.get_last_modified().as_deref(),
+            Some("Tue, 14 Nov 2023 22:13:20 GMT")
+        );
+    }
+
+    #[test]
+    fn is_fresh_test() {
+        let validators = validators();
+        let if_none_match = |v: &str| CacheConditions {
+            if_none_match: Some(v.to_owned()),
+            if_modified_since: Some(1700000000),
+        };
+        assert!(validators.is_fresh(&if_none_match(&validators.get_etag())));
+        assert!(validators.is_fresh(&if_none_match(&format!(
+            "\"other\", \"{}\"",
+            validators.tag
+        ))));
+        assert!(validators.is_fresh(&if_none_match("*")));
+        // a stale tag wins over a matching date
+        assert!(!validators.is_fresh(&if_none_match("W/\"other\"")));
+
+        let if_modified_since = |v: i64| CacheConditions {
+            if_none_match: None,
+            if_modified_since: Some(v),
+        };
+        assert!(validators.is_fresh(&if_modified_since(1700000000)));
+        assert!(!validators.is_fresh(&if_modified_since(1699999999)));
+        assert!(!validators.is_fresh(&CacheConditions::default()));
+    }
+}
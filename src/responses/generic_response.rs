@@ -6,6 +6,9 @@ use rocket_okapi::okapi::schemars::JsonSchema;
 use rocket_okapi::okapi::schemars::{self, Map};
 use rocket_okapi::response::OpenApiResponderInner;
 
+use super::cached_response::{CacheValidators, WithValidators};
+use crate::dto::cache_conditions::CacheConditions;
+
 #[derive(Responder, JsonSchema)]
 pub enum Responses<T, E> {
     #[response(status = 201, content_type = "json")]
@@ -14,6 +17,34 @@ pub enum Responses<T, E> {
     Sucess(T),
     #[response(status = 400, content_type = "json")]
     BadRequest(E),
+    #[response(status = 404, content_type = "json")]
+    NotFound(E),
+    /// `Sucess` along with its `ETag` and `Last-Modified`
+    #[schemars(skip)]
+    #[response(status = 200, content_type = "json")]
+    Cached(WithValidators<T>),
+    #[schemars(skip)]
+    #[response(status = 304)]
+    NotModified(WithValidators<()>),
+}
+
+impl<T, E> Responses<T, E> {
+    /// Successful response carrying its cache validators, or an empty `304 Not Modified` when the
+    /// client conditions show it already holds the content. Without validators it is a plain
+    /// `Sucess`. Endpoints returning it are wrapped in `Cacheable` to document the `304`
+    pub fn cached(
+        body: T,
+        validators: Option<CacheValidators>,
+        conditions: &CacheConditions,
+    ) -> Self {
+        match validators {
+            Some(validators) if validators.is_fresh(conditions) => {
+                Responses::NotModified(WithValidators((), validators))
+            }
+            Some(validators) => Responses::Cached(WithValidators(body, validators)),
+            None => Responses::Sucess(body),
+        }
+    }
 }
 
 impl<T, E> OpenApiResponderInner for Responses<T, E> {
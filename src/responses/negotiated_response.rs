@@ -0,0 +1,37 @@
+use rocket::response::Responder;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::{RefOr, Response as OpenApiReponse};
+use rocket_okapi::response::OpenApiResponderInner;
+
+use super::generic_response::Responses;
+
+/// Usual json responses, or the FHIR resource alone when the client asked for FHIR
+#[derive(Responder)]
+pub enum NegotiatedResponses<T, F, E> {
+    Json(Responses<T, E>),
+    #[response(status = 200, content_type = "application/fhir+json")]
+    Fhir(F),
+    /// FHIR was asked for a result that has no FHIR representation
+    #[response(status = 406, content_type = "json")]
+    NotAcceptable(E),
+}
+
+impl<T, F, E> OpenApiResponderInner for NegotiatedResponses<T, F, E> {
+    fn responses(
+        gen: &mut OpenApiGenerator,
+    ) -> rocket_okapi::Result<rocket_okapi::okapi::openapi3::Responses> {
+        let mut responses = Responses::<T, E>::responses(gen)?;
+        responses.responses.insert(
+            "406".to_string(),
+            RefOr::Object(OpenApiReponse {
+                description: "\
+                # [406 Not Acceptable](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/406)\n\
+                FHIR was requested (`format=fhir` or `Accept: application/fhir+json`) but the certificate has no FHIR representation. \
+                "
+                .to_string(),
+                ..Default::default()
+            }),
+        );
+        Ok(responses)
+    }
+}
@@ -1,8 +1,15 @@
 pub mod did;
+pub mod disclosure;
+#[cfg(feature = "issuance")]
+pub mod issuer;
 pub mod pd_did_member;
 pub mod pd_member;
+pub mod policy;
 pub mod public_directory;
 pub mod public_key;
+pub mod receipt;
+pub mod signing;
+pub mod trust_list;
 pub mod trusted_registry;
 pub mod validator;
 pub mod web3;
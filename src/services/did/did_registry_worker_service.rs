@@ -5,16 +5,18 @@ use crate::{
         did::data_interface::DidDataInterfaceService,
         pd_member::data_interface::PdMemberDataInterfaceService,
         public_key::data_interface::PublicKeyService,
+        trust_list::data_interface::{KeyChangeType, TrustListVersionService},
         trusted_registry::trusted_registry::Contract,
         web3::utils::{
             get_address_from_log, get_bool_from_log, get_bytes_from_log, get_u64_from_log,
         },
-        x509::x509_utils::X509Utils,
+        x509::csca_trust_store::{CscaTrustStore, DscChainStatus},
     },
 };
 use crypto::{digest::Digest, sha3::Sha3};
+use lacpass_verifier::x509::{DccCertificateType, X509Utils};
 use log::{debug, info};
-use sea_orm::DatabaseConnection;
+use sea_orm::{DatabaseConnection, TransactionTrait};
 use uuid::Uuid;
 use web3::ethabi::Log;
 
@@ -267,7 +269,10 @@ impl DidRegistryWorkerService {
             }
             let valid_to = get_u64_from_log(&did_attribute_changed_log, "validTo");
             // let change_time = get_u64_from_log(&did_attribute_changed_log, "changeTime"); // Not needed for this logic
-            let is_compromised = get_bool_from_log(&did_attribute_changed_log, "compromised"); // TODO: analyze how to serve this
+            let is_compromised = get_bool_from_log(&did_attribute_changed_log, "compromised");
+            // when revoking a compromised key the registry sets validTo to the time since when the key
+            // is considered compromised (block time minus revokeDeltaTime)
+            let compromised_at = if is_compromised { Some(valid_to) } else { None };
 
             let jwk_bytes = get_bytes_from_log(&did_attribute_changed_log, "value");
             let string_data;
@@ -295,6 +300,10 @@ impl DidRegistryWorkerService {
             // endpoint for CRL ~
 
             let mut h = Sha3::keccak256();
+            let kid: Option<String>;
+            let is_chain_trusted: Option<bool>;
+            let certificate_types: Option<String>;
+            let jwk_thumbprint: Option<String>;
             match serde_json::from_str::<Jwk>(&string_data) {
                 Ok(jwk) => match jwk.x5c {
                     Some(x5c) => match x5c.get(0) {
@@ -302,6 +311,30 @@ impl DidRegistryWorkerService {
                             match X509Utils::get_decoded_pem_bytes(pem_candidate.to_string()) {
                                 Ok(decoded) => {
                                     h.input(&decoded);
+                                    kid = X509Utils::get_kid_string_from_pem(
+                                        pem_candidate.to_string(),
+                                    )
+                                    .ok();
+                                    jwk_thumbprint = X509Utils::get_jwk_thumbprint_from_pem(
+                                        pem_candidate.to_string(),
+                                    )
+                                    .ok();
+                                    let chain_status = CscaTrustStore::get()
+                                        .validate_dsc(&self.country_code, pem_candidate);
+                                    if let DscChainStatus::Untrusted(e) = &chain_status {
+                                        info!(
+                                            "Flagging public key from did {}: {}",
+                                            self.did.did, e
+                                        );
+                                    }
+                                    is_chain_trusted = chain_status.is_trusted();
+                                    certificate_types =
+                                        X509Utils::get_dcc_certificate_types_from_pem(
+                                            pem_candidate.to_string(),
+                                        )
+                                        .ok()
+                                        .flatten()
+                                        .map(|types| DccCertificateType::to_codes(&types));
                                 }
                                 Err(e) => {
                                     debug!(
@@ -346,18 +379,29 @@ impl DidRegistryWorkerService {
                     Some(found_public_key) => match found_public_key.block_number {
                         Some(block_number) => {
                             if (block_number as u64) < *block {
+                                // the key and its trust list change are committed together
+                                let txn = db.begin().await?;
                                 match self
                                     .public_key_service
                                     .update_public_key(
-                                        db,
+                                        &txn,
                                         &found_public_key.id,
                                         Some(*block),
                                         Some(valid_to),
                                         Some(is_compromised),
+                                        compromised_at,
+                                        Some(is_chain_trusted),
                                     )
                                     .await
                                 {
                                     Ok(_) => {
+                                        TrustListVersionService::bump_version(
+                                            &txn,
+                                            &found_public_key.id,
+                                            KeyChangeType::Updated,
+                                        )
+                                        .await?;
+                                        txn.commit().await?;
                                         info!(
                                             "Updated public key with id: {:} for did: {}",
                                             found_public_key.id, self.did.did
@@ -382,22 +426,35 @@ impl DidRegistryWorkerService {
                         }
                     },
                     None => {
+                        let txn = db.begin().await?;
                         match self
                             .public_key_service
                             .insert_public_key(
-                                db,
+                                &txn,
                                 Some(self.did.id),
                                 Some(*block as i64),
                                 jwk_bytes,
                                 &content_hash,
                                 &valid_to,
                                 Some(is_compromised),
+                                compromised_at,
                                 &self.country_code,
                                 self.url.clone(),
+                                kid,
+                                is_chain_trusted,
+                                certificate_types,
+                                jwk_thumbprint,
                             )
                             .await
                         {
-                            Ok(_) => {
+                            Ok(inserted) => {
+                                TrustListVersionService::bump_version(
+                                    &txn,
+                                    &inserted.id,
+                                    KeyChangeType::Added,
+                                )
+                                .await?;
+                                txn.commit().await?;
                                 info!("Inserted new public key for did: {}", self.did.did);
                             }
                             Err(e) => return Err(e.into()),
@@ -0,0 +1,2 @@
+pub mod api_clients;
+pub mod disclosure_profile;
@@ -0,0 +1,137 @@
+use std::fs;
+
+use clap::__macro_refs::once_cell::sync::OnceCell;
+use log::info;
+use openssl::sha::sha256;
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::{services::disclosure::disclosure_profile::DisclosureProfile, utils::utils::Utils};
+
+/// clients loaded once from the file pointed by `API_CLIENTS_FILE` (json or toml)
+static API_CLIENTS: OnceCell<ApiClients> = OnceCell::new();
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "camelCase")]
+pub struct ApiClient {
+    pub name: String,
+    /// hex encoded sha-256 of the key sent in the `X-Api-Key` header
+    pub api_key_sha256: String,
+    /// most disclosing profile the client may get
+    pub disclosure: DisclosureProfile,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ApiClientsFile {
+    #[serde(default)]
+    pub clients: Vec<ApiClient>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiClients {
+    clients: Vec<ApiClient>,
+    /// profile of the requests without api key, `DEFAULT_DISCLOSURE_PROFILE` (full if not set)
+    default_disclosure: DisclosureProfile,
+}
+
+impl ApiClients {
+    pub fn new(clients: Vec<ApiClient>, default_disclosure: DisclosureProfile) -> Self {
+        ApiClients {
+            clients,
+            default_disclosure,
+        }
+    }
+
+    pub fn from_file_content(path: &str, content: &str) -> anyhow::Result<Vec<ApiClient>> {
+        let file: ApiClientsFile = if path.ends_with(".toml") {
+            toml::from_str(content)?
+        } else {
+            serde_json::from_str(content)?
+        };
+        Ok(file.clients)
+    }
+
+    pub fn get() -> &'static ApiClients {
+        API_CLIENTS.get_or_init(|| {
+            let default_disclosure = match Utils::get_env_or_err("DEFAULT_DISCLOSURE_PROFILE") {
+                Ok(v) => match serde_json::from_value(serde_json::Value::from(v.clone())) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        info!(
+                            "Invalid DEFAULT_DISCLOSURE_PROFILE {}, using validity-only, error was: {:?}",
+                            v, e
+                        );
+                        DisclosureProfile::ValidityOnly
+                    }
+                },
+                Err(_) => DisclosureProfile::Full,
+            };
+            let path = match Utils::get_env_or_err("API_CLIENTS_FILE") {
+                Ok(v) => v,
+                Err(_) => return ApiClients::new(vec![], default_disclosure),
+            };
+            let clients = match fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Self::from_file_content(&path, &content))
+            {
+                Ok(v) => {
+                    info!("Loaded {} api clients from {}", v.len(), path);
+                    v
+                }
+                Err(e) => {
+                    info!("Unable to load api clients file {}, error was: {:?}", path, e);
+                    vec![]
+                }
+            };
+            ApiClients::new(clients, default_disclosure)
+        })
+    }
+
+    pub fn find_by_api_key(&self, api_key: &str) -> Option<&ApiClient> {
+        let digest = hex::encode(sha256(api_key.as_bytes()));
+        self.clients
+            .iter()
+            .find(|client| client.api_key_sha256.eq_ignore_ascii_case(&digest))
+    }
+
+    pub fn default_disclosure(&self) -> DisclosureProfile {
+        self.default_disclosure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENTS_TOML: &str = r#"
+        [[clients]]
+        name = "venue-scanners"
+        # sha-256 of "venue-key"
+        apiKeySha256 = "2f6e5acd7bc9451d373fabb18dd6b98c63131509a343244712cafca3e7cc50fc"
+        disclosure = "minimal"
+    "#;
+
+    #[test]
+    fn find_by_api_key_test() {
+        let clients = ApiClients::from_file_content("clients.toml", CLIENTS_TOML).unwrap();
+        assert_eq!(clients[0].disclosure, DisclosureProfile::Minimal);
+        let api_clients = ApiClients::new(clients, DisclosureProfile::Full);
+        assert_eq!(
+            api_clients.find_by_api_key("venue-key").unwrap().name,
+            "venue-scanners"
+        );
+        assert!(api_clients.find_by_api_key("other-key").is_none());
+        assert_eq!(api_clients.default_disclosure(), DisclosureProfile::Full);
+    }
+
+    #[test]
+    fn from_json_file_content_test() {
+        let clients = ApiClients::from_file_content(
+            "clients.json",
+            r#"{"clients": [{"name": "border", "apiKeySha256": "00", "disclosure": "validity-only"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(clients[0].disclosure, DisclosureProfile::ValidityOnly);
+    }
+}
@@ -0,0 +1,212 @@
+use clap::__macro_refs::once_cell::sync::OnceCell;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::FromFormField;
+use rocket_okapi::okapi::schemars::{self, JsonSchema};
+
+use crate::{
+    dto::response::{
+        hc1_response_dto::{DdccCoreDataSet, EuDigitalCovidCertificate, HC1ValidationResponseDto},
+        shc_response_dto::ShcValidationResponseDto,
+        vds_nc_response_dto::VdsNcValidationResponseDto,
+    },
+    utils::utils::Utils,
+};
+
+/// `LOG_PERSONAL_DATA`, read once
+static LOG_PERSONAL_DATA: OnceCell<bool> = OnceCell::new();
+
+/// Personal data (certificate content) is only written to the logs when `LOG_PERSONAL_DATA` is
+/// set to `true`
+pub fn log_personal_data() -> bool {
+    *LOG_PERSONAL_DATA.get_or_init(|| {
+        Utils::get_env_or_err("LOG_PERSONAL_DATA")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    })
+}
+
+/// How much of the certificate content is returned along with the verification outcome.
+/// Variants are ordered from the least to the most disclosing
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+    FromFormField,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "kebab-case")]
+pub enum DisclosureProfile {
+    /// outcome of the checks and policy only, no personal data
+    #[field(value = "validity-only")]
+    ValidityOnly,
+    /// name and birth year of the holder
+    #[field(value = "minimal")]
+    Minimal,
+    /// the whole certificate content
+    #[field(value = "full")]
+    Full,
+}
+
+impl DisclosureProfile {
+    /// The requested profile, if any, is capped by the one allowed to the caller
+    pub fn effective(requested: Option<DisclosureProfile>, allowed: DisclosureProfile) -> Self {
+        requested.map_or(allowed, |requested| requested.min(allowed))
+    }
+
+    /// Removes from the verification result what the profile does not disclose
+    pub fn apply(&self, mut result: HC1ValidationResponseDto) -> HC1ValidationResponseDto {
+        match self {
+            DisclosureProfile::Full => {}
+            DisclosureProfile::Minimal => {
+                result.ddcc_core_data_set = result.ddcc_core_data_set.map(minimal_ddcc);
+                result.eu_dcc = result.eu_dcc.map(minimal_eu_dcc);
+            }
+            DisclosureProfile::ValidityOnly => {
+                result.ddcc_core_data_set = None;
+                result.eu_dcc = None;
+            }
+        }
+        result
+    }
+
+    pub fn apply_vds_nc(
+        &self,
+        mut result: VdsNcValidationResponseDto,
+    ) -> VdsNcValidationResponseDto {
+        match self {
+            DisclosureProfile::Full => {}
+            DisclosureProfile::Minimal => {
+                result.uvci = None;
+                result.birth_date = result.birth_date.as_deref().and_then(birth_year);
+                result.vaccinations = vec![];
+            }
+            DisclosureProfile::ValidityOnly => {
+                result.uvci = None;
+                result.name = None;
+                result.birth_date = None;
+                result.vaccinations = vec![];
+            }
+        }
+        result
+    }
+
+    /// SMART Health Cards results carry no holder name, only the immunizations
+    pub fn apply_shc(&self, mut result: ShcValidationResponseDto) -> ShcValidationResponseDto {
+        if *self != DisclosureProfile::Full {
+            result.immunizations = vec![];
+        }
+        result
+    }
+}
+
+/// Dates are expected as `YYYY-MM-DD` (or partial dates starting with the year)
+fn birth_year(date: &str) -> Option<String> {
+    date.get(..4)
+        .filter(|year| year.chars().all(|c| c.is_ascii_digit()))
+        .map(|year| year.to_owned())
+}
+
+fn minimal_ddcc(ddcc_core_data_set: DdccCoreDataSet) -> DdccCoreDataSet {
+    DdccCoreDataSet {
+        vaccination: None,
+        test_result: None,
+        recovery: None,
+        resource_type: None,
+        birth_date: ddcc_core_data_set
+            .birth_date
+            .as_deref()
+            .and_then(birth_year),
+        name: ddcc_core_data_set.name,
+        identifier: None,
+        sex: None,
+        certificate: None,
+        extensions: Default::default(),
+    }
+}
+
+fn minimal_eu_dcc(eu_dcc: EuDigitalCovidCertificate) -> EuDigitalCovidCertificate {
+    EuDigitalCovidCertificate {
+        version: eu_dcc.version,
+        name: eu_dcc.name,
+        date_of_birth: birth_year(&eu_dcc.date_of_birth).unwrap_or_default(),
+        vaccinations: None,
+        tests: None,
+        recoveries: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::response::hc1_response_dto::PayloadType;
+
+    use super::*;
+
+    fn validation_response() -> HC1ValidationResponseDto {
+        HC1ValidationResponseDto {
+            is_valid: true,
+            kid: Some("a2lk".to_owned()),
+            checks: vec![],
+            payload_type: Some(PayloadType::Ddcc),
+            ddcc_core_data_set: Some(DdccCoreDataSet {
+                vaccination: None,
+                test_result: None,
+                recovery: None,
+                resource_type: None,
+                birth_date: Some("1990-01-01".to_owned()),
+                name: "Jane Doe".to_owned(),
+                identifier: Some("123456".to_owned()),
+                sex: Some("female".to_owned()),
+                certificate: None,
+                extensions: Default::default(),
+            }),
+            eu_dcc: None,
+            policy: None,
+            receipt: None,
+        }
+    }
+
+    #[test]
+    fn effective_test() {
+        use DisclosureProfile::*;
+        assert_eq!(DisclosureProfile::effective(None, Minimal), Minimal);
+        assert_eq!(DisclosureProfile::effective(Some(Full), Minimal), Minimal);
+        assert_eq!(
+            DisclosureProfile::effective(Some(ValidityOnly), Full),
+            ValidityOnly
+        );
+    }
+
+    #[test]
+    fn apply_minimal_test() {
+        let result = DisclosureProfile::Minimal.apply(validation_response());
+        let ddcc = result.ddcc_core_data_set.unwrap();
+        assert_eq!(ddcc.name, "Jane Doe");
+        assert_eq!(ddcc.birth_date.as_deref(), Some("1990"));
+        assert!(ddcc.identifier.is_none());
+        assert!(ddcc.sex.is_none());
+        assert!(result.is_valid);
+        assert_eq!(result.kid.as_deref(), Some("a2lk"));
+    }
+
+    #[test]
+    fn apply_validity_only_test() {
+        let result = DisclosureProfile::ValidityOnly.apply(validation_response());
+        assert!(result.ddcc_core_data_set.is_none());
+        assert!(result.eu_dcc.is_none());
+        assert_eq!(result.payload_type, Some(PayloadType::Ddcc));
+    }
+
+    #[test]
+    fn birth_year_test() {
+        assert_eq!(birth_year("1990-01-01").as_deref(), Some("1990"));
+        assert_eq!(birth_year("1990").as_deref(), Some("1990"));
+        assert_eq!(birth_year("01/01/1990"), None);
+    }
+}
@@ -0,0 +1 @@
+pub mod hc1_issuer_service;
@@ -0,0 +1,285 @@
+use std::fs;
+
+use ciborium::value::Value as CborValue;
+use lacpass_verifier::{
+    country_code::normalize_country_code,
+    signer::{get_ddcc_cwt_payload, Hc1Signer},
+};
+use log::{debug, info};
+use openssl::pkey::PKey;
+use rocket::serde::json::Json;
+use uuid::Uuid;
+
+use crate::{
+    dto::response::{
+        hc1_response_dto::DdccCoreDataSet, issue_hc1_response_dto::IssueHc1ResponseDto,
+    },
+    responses::{
+        error_message::ErrorMessage, generic_response::Responses, success_messages::SuccessMessage,
+    },
+    utils::utils::Utils,
+};
+
+/// validity of the issued certificates when none is requested
+const DEFAULT_VALIDITY_DAYS: i64 = 365;
+
+/// Signs DDCC payloads into HC1 (COSE_Sign1, zlib, base45) certificates with a locally
+/// configured DSC; meant to produce test vectors, never to be enabled in production
+pub struct Hc1Issuer {
+    signer: Hc1Signer,
+    /// iso alpha3 code set as the `iss` claim
+    country_code: String,
+}
+
+impl Hc1Issuer {
+    pub fn new(pem: String, private_key_pem: &[u8], country_code: &str) -> anyhow::Result<Self> {
+        let country_code = match normalize_country_code(country_code) {
+            Some(v) => v,
+            None => return Err(anyhow::anyhow!("Invalid issuer country {}", country_code)),
+        };
+        let private_key = PKey::private_key_from_pem(private_key_pem)?;
+        Ok(Hc1Issuer {
+            signer: Hc1Signer::new(pem, private_key)?,
+            country_code,
+        })
+    }
+
+    /// Loads the issuer from `ISSUER_CERTIFICATE_FILE`, `ISSUER_PRIVATE_KEY_FILE` (PKCS#8 pem)
+    /// and `ISSUER_COUNTRY`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let read = |env_name: &str| -> anyhow::Result<String> {
+            let path = Utils::get_env_or_err(env_name).map_err(|e| anyhow::anyhow!(e))?;
+            Ok(fs::read_to_string(path)?)
+        };
+        let pem = read("ISSUER_CERTIFICATE_FILE")?;
+        let private_key_pem = read("ISSUER_PRIVATE_KEY_FILE")?;
+        let country_code =
+            Utils::get_env_or_err("ISSUER_COUNTRY").map_err(|e| anyhow::anyhow!(e))?;
+        Self::new(pem, private_key_pem.as_bytes(), &country_code)
+    }
+
+    pub fn get_kid(&self) -> anyhow::Result<String> {
+        self.signer.get_kid()
+    }
+
+    /// CWT payload: iss, iat, exp and the DDCC under the hcert claim
+    pub fn get_payload(
+        &self,
+        ddcc_core_data_set: &DdccCoreDataSet,
+        iat: i64,
+        exp: i64,
+    ) -> anyhow::Result<Vec<u8>> {
+        let ddcc = match CborValue::serialized(ddcc_core_data_set)? {
+            // the test result section is named "test" in the DDCC payload
+            CborValue::Map(entries) => CborValue::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| match k {
+                        CborValue::Text(name) if name == "testResult" => {
+                            (CborValue::Text("test".to_owned()), v)
+                        }
+                        k => (k, v),
+                    })
+                    .collect(),
+            ),
+            _ => return Err(anyhow::anyhow!("DDCC core data set is not a map")),
+        };
+        get_ddcc_cwt_payload(&self.country_code, iat, exp, ddcc)
+    }
+
+    /// Returns the "HC1:" prefixed base45 certificate
+    pub fn issue(
+        &self,
+        ddcc_core_data_set: &DdccCoreDataSet,
+        iat: i64,
+        exp: i64,
+    ) -> anyhow::Result<String> {
+        self.signer
+            .sign(self.get_payload(ddcc_core_data_set, iat, exp)?)
+    }
+}
+
+/// `iat` plus `validity_days`, `None` when it does not fit in an i64
+fn get_exp(iat: i64, validity_days: i64) -> Option<i64> {
+    validity_days
+        .checked_mul(24 * 60 * 60)
+        .and_then(|validity| iat.checked_add(validity))
+}
+
+pub async fn issue_base45(
+    ddcc_core_data_set: DdccCoreDataSet,
+    validity_days: Option<i64>,
+) -> Responses<Json<SuccessMessage<IssueHc1ResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id: Uuid = Uuid::new_v4();
+    info!("New issuance request: {:?}", trace_id);
+    let bad_request = |message: &'static str| {
+        Responses::BadRequest(Json::from(ErrorMessage {
+            message,
+            trace_id: trace_id.to_string(),
+        }))
+    };
+    let validity_days = validity_days.unwrap_or(DEFAULT_VALIDITY_DAYS);
+    if validity_days <= 0 {
+        return bad_request("'validity_days' must be greater than zero");
+    }
+    let issuer = match Hc1Issuer::from_env() {
+        Ok(v) => v,
+        Err(e) => {
+            debug!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, e);
+            return bad_request("Issuer is not configured");
+        }
+    };
+    let iat = chrono::Utc::now().timestamp();
+    let exp = match get_exp(iat, validity_days) {
+        Some(v) => v,
+        None => return bad_request("'validity_days' is too large"),
+    };
+    match (
+        issuer.issue(&ddcc_core_data_set, iat, exp),
+        issuer.get_kid(),
+    ) {
+        (Ok(data), Ok(kid)) => Responses::Sucess(Json::from(SuccessMessage {
+            data: IssueHc1ResponseDto {
+                data,
+                kid,
+                iat,
+                exp,
+            },
+            trace_id: trace_id.to_string(),
+        })),
+        (Err(e), _) | (_, Err(e)) => {
+            debug!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, e);
+            bad_request("Unable to issue certificate")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cose::{algs, message::CoseMessage};
+    use lacpass_verifier::{
+        cwt_claims::CwtClaims,
+        hc1::{decode_hc1, verify_hc1},
+        key_provider::{InMemoryKeyProvider, TrustedKey},
+        test_support::{ec_key, self_signed_pem},
+        x509::X509Utils,
+    };
+    use openssl::{
+        nid::Nid,
+        pkey::{PKey, Private},
+    };
+
+    use crate::dto::response::hc1_response_dto::{CodeSystem, PayloadType, Vaccination};
+
+    use super::*;
+
+    const IAT: i64 = 1696118400;
+    const EXP: i64 = 1727740800;
+
+    fn code_system(code: &str) -> CodeSystem {
+        CodeSystem {
+            code: Some(code.to_owned()),
+            system: None,
+        }
+    }
+
+    fn ddcc_core_data_set() -> DdccCoreDataSet {
+        DdccCoreDataSet {
+            vaccination: Some(Vaccination {
+                date: "2023-09-01".to_owned(),
+                dose: 2,
+                vaccine: code_system("XM68M6"),
+                country: code_system("CHL"),
+                maholder: None,
+                lot: "VC35679".to_owned(),
+                centre: None,
+                brand: code_system("XM8NQ0"),
+                manufacturer: None,
+                valid_from: None,
+                total_doses: Some(2),
+                practitioner: None,
+                disease: None,
+                next_dose: None,
+                extensions: Default::default(),
+            }),
+            test_result: None,
+            recovery: None,
+            resource_type: None,
+            birth_date: Some("1990-01-01".to_owned()),
+            name: "Jane Doe".to_owned(),
+            identifier: None,
+            sex: None,
+            certificate: None,
+            extensions: Default::default(),
+        }
+    }
+
+    /// issues a certificate with a self signed DSC and runs it through the verifier with that
+    /// DSC as the only trusted key
+    async fn issue_and_verify(private_key: PKey<Private>, alg: i32) {
+        let pem = self_signed_pem(&private_key);
+        let issuer = Hc1Issuer::new(
+            pem.clone(),
+            &private_key.private_key_to_pem_pkcs8().unwrap(),
+            "CL",
+        )
+        .unwrap();
+        let data = issuer.issue(&ddcc_core_data_set(), IAT, EXP).unwrap();
+        assert!(data.starts_with("HC1:"));
+
+        let mut sign1 = CoseMessage::new_sign();
+        sign1.bytes = decode_hc1(&data).unwrap();
+        sign1.init_decoder(None).unwrap();
+        assert_eq!(sign1.header.alg, Some(alg));
+        let claims = CwtClaims::from_payload(&sign1.payload).unwrap();
+        assert_eq!(claims.iss.as_deref(), Some("CHL"));
+        assert_eq!(claims.iat, Some(IAT));
+        assert_eq!(claims.exp, Some(EXP));
+
+        let kid = X509Utils::get_kid_string_from_pem(pem.clone()).unwrap();
+        let mut key_provider = InMemoryKeyProvider::new(vec![TrustedKey {
+            kid: kid.clone(),
+            country_code: "CHL".to_owned(),
+            exp: Some(X509Utils::get_expiration_from_pem(pem.clone()).unwrap() as i64),
+            pem,
+            is_compromised: false,
+            compromised_at: None,
+            certificate_types: None,
+            is_chain_trusted: None,
+        }]);
+        let verification = verify_hc1(&mut key_provider, &data, IAT + 3600)
+            .await
+            .unwrap();
+        assert!(verification.is_valid, "{:?}", verification.checks);
+        assert_eq!(verification.kid, Some(kid));
+        assert_eq!(verification.payload_type, Some(PayloadType::Ddcc));
+        let ddcc = verification.ddcc_core_data_set.unwrap();
+        assert_eq!(ddcc.name, "Jane Doe");
+        assert_eq!(ddcc.vaccination.unwrap().lot, "VC35679");
+    }
+
+    #[rocket::async_test]
+    async fn issue_es256_test() {
+        issue_and_verify(ec_key(Nid::X9_62_PRIME256V1), algs::ES256).await;
+    }
+
+    #[rocket::async_test]
+    async fn issue_eddsa_test() {
+        issue_and_verify(PKey::generate_ed25519().unwrap(), algs::EDDSA).await;
+    }
+
+    #[test]
+    fn get_exp_test() {
+        assert_eq!(get_exp(IAT, 1), Some(IAT + 24 * 60 * 60));
+        assert_eq!(get_exp(IAT, i64::MAX / (24 * 60 * 60)), None);
+        assert_eq!(get_exp(IAT, i64::MAX), None);
+    }
+
+    #[test]
+    fn mismatching_private_key_test() {
+        let private_key = PKey::generate_ed25519().unwrap();
+        let pem = self_signed_pem(&private_key);
+        let other_key = PKey::generate_ed25519().unwrap();
+        assert!(Hc1Issuer::new(pem, &other_key.private_key_to_pem_pkcs8().unwrap(), "CL").is_err());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod data_interface;
+pub mod policy_service;
+pub mod rules;
@@ -0,0 +1,14 @@
+use sea_orm::DatabaseConnection;
+
+use crate::entities::{entities::PolicyEntity, models::PolicyModel};
+
+pub struct PolicyService {}
+
+impl PolicyService {
+    pub async fn find_policy_by_name(
+        db: &DatabaseConnection,
+        name: &str,
+    ) -> Result<Option<PolicyModel>, sea_orm::DbErr> {
+        PolicyEntity::find_by_name(name).one(db).await
+    }
+}
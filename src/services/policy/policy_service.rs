@@ -0,0 +1,73 @@
+use std::fs;
+
+use clap::__macro_refs::once_cell::sync::OnceCell;
+use log::{debug, info};
+use sea_orm::DatabaseConnection;
+
+use crate::{
+    services::policy::{
+        data_interface::PolicyService,
+        rules::{Policy, PolicyFile, Rule},
+    },
+    utils::utils::Utils,
+};
+
+/// policies loaded once from the file pointed by `POLICIES_FILE` (json or toml)
+static FILE_POLICIES: OnceCell<Vec<Policy>> = OnceCell::new();
+
+fn parse_policy_file(path: &str, content: &str) -> anyhow::Result<PolicyFile> {
+    if path.ends_with(".toml") {
+        Ok(toml::from_str(content)?)
+    } else {
+        Ok(serde_json::from_str(content)?)
+    }
+}
+
+fn get_file_policies() -> &'static Vec<Policy> {
+    FILE_POLICIES.get_or_init(|| {
+        let path = match Utils::get_env_or_err("POLICIES_FILE") {
+            Ok(v) => v,
+            Err(_) => return vec![],
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(v) => v,
+            Err(e) => {
+                info!("Unable to read policies file {}, error was: {:?}", path, e);
+                return vec![];
+            }
+        };
+        match parse_policy_file(&path, &content) {
+            Ok(v) => {
+                info!("Loaded {} policies from {}", v.policies.len(), path);
+                v.policies
+            }
+            Err(e) => {
+                info!("Invalid policies file {}, error was: {:?}", path, e);
+                vec![]
+            }
+        }
+    })
+}
+
+/// Looks the policy up in the database first, then in the policies file
+pub async fn get_policy(db: &DatabaseConnection, name: &str) -> anyhow::Result<Option<Policy>> {
+    if let Some(found) = PolicyService::find_policy_by_name(db, name).await? {
+        let rules: Vec<Rule> = match serde_json::from_str(&found.rules) {
+            Ok(v) => v,
+            Err(e) => {
+                let message = format!("Invalid rules for policy {}, error was: {:?}", name, e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        };
+        return Ok(Some(Policy {
+            name: found.name,
+            description: found.description,
+            rules,
+        }));
+    }
+    Ok(get_file_policies()
+        .iter()
+        .find(|policy| policy.name == name)
+        .cloned())
+}
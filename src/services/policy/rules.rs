@@ -0,0 +1,309 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::response::{
+    hc1_response_dto::{CheckStatus, DdccCoreDataSet, Vaccination},
+    policy_response_dto::{PolicyEvaluationDto, RuleResultDto},
+};
+
+/// A named set of business rules a certificate must satisfy to be acceptable for a given use
+/// (e.g. the entry requirements of a destination country)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Policy {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub rules: Vec<Rule>,
+}
+
+/// Content of the policies file (json or toml)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyFile {
+    pub policies: Vec<Policy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rule {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub condition: RuleCondition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleCondition {
+    /// `dose >= totalDoses`
+    FullSeriesCompleted,
+    /// the last dose was given at least `days` ago
+    MinDaysSinceLastDose { days: i64 },
+    /// the last dose was given at most `days` ago
+    MaxDaysSinceLastDose { days: i64 },
+    /// vaccine code (e.g. ICD-11) among `codes`
+    AllowedVaccineCodes { codes: Vec<String> },
+    /// brand (product) code among `codes`, e.g. the WHO EUL list
+    AllowedBrandCodes { codes: Vec<String> },
+}
+
+impl Rule {
+    fn result(&self, status: CheckStatus, message: Option<String>) -> RuleResultDto {
+        RuleResultDto {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            status,
+            message,
+        }
+    }
+
+    pub fn evaluate(
+        &self,
+        ddcc_core_data_set: &DdccCoreDataSet,
+        today: NaiveDate,
+    ) -> RuleResultDto {
+        let vaccination = match &ddcc_core_data_set.vaccination {
+            Some(v) => v,
+            None => {
+                return self.result(
+                    CheckStatus::Failed,
+                    Some("No vaccination found in certificate".to_owned()),
+                )
+            }
+        };
+        match self.condition.check(vaccination, today) {
+            Ok(()) => self.result(CheckStatus::Passed, None),
+            Err(message) => self.result(CheckStatus::Failed, Some(message)),
+        }
+    }
+}
+
+impl RuleCondition {
+    /// Returns why the vaccination does not satisfy the condition
+    pub fn check(&self, vaccination: &Vaccination, today: NaiveDate) -> Result<(), String> {
+        match self {
+            RuleCondition::FullSeriesCompleted => match vaccination.total_doses {
+                Some(total_doses) if vaccination.dose >= total_doses => Ok(()),
+                Some(total_doses) => Err(format!(
+                    "Dose {} of {}, series not completed",
+                    vaccination.dose, total_doses
+                )),
+                None => Err("Total doses unknown".to_owned()),
+            },
+            RuleCondition::MinDaysSinceLastDose { days } => {
+                let elapsed = Self::days_since(&vaccination.date, today)?;
+                if elapsed >= *days {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Last dose given {} days ago, at least {} required",
+                        elapsed, days
+                    ))
+                }
+            }
+            RuleCondition::MaxDaysSinceLastDose { days } => {
+                let elapsed = Self::days_since(&vaccination.date, today)?;
+                if elapsed <= *days {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Last dose given {} days ago, at most {} allowed",
+                        elapsed, days
+                    ))
+                }
+            }
+            RuleCondition::AllowedVaccineCodes { codes } => {
+                Self::check_code(vaccination.vaccine.code.as_ref(), codes, "Vaccine")
+            }
+            RuleCondition::AllowedBrandCodes { codes } => {
+                Self::check_code(vaccination.brand.code.as_ref(), codes, "Brand")
+            }
+        }
+    }
+
+    fn check_code(code: Option<&String>, codes: &[String], name: &str) -> Result<(), String> {
+        match code {
+            Some(code) if codes.contains(code) => Ok(()),
+            Some(code) => Err(format!("{} code {} is not allowed", name, code)),
+            None => Err(format!("{} code not found", name)),
+        }
+    }
+
+    /// Dates come as `YYYY-MM-DD`, optionally followed by a time
+    fn days_since(date: &str, today: NaiveDate) -> Result<i64, String> {
+        match date
+            .get(..10)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        {
+            Some(date) => Ok((today - date).num_days()),
+            None => Err(format!("Invalid vaccination date: {}", date)),
+        }
+    }
+}
+
+impl Policy {
+    /// A certificate without a DDCC core data set (e.g. an EU DCC) does not pass the policy
+    pub fn evaluate(
+        &self,
+        ddcc_core_data_set: Option<&DdccCoreDataSet>,
+        today: NaiveDate,
+    ) -> PolicyEvaluationDto {
+        let ddcc_core_data_set = match ddcc_core_data_set {
+            Some(v) => v,
+            None => return self.not_applicable("No DDCC core data set to evaluate"),
+        };
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| rule.evaluate(ddcc_core_data_set, today))
+            .collect::<Vec<_>>();
+        PolicyEvaluationDto {
+            policy: self.name.clone(),
+            passed: rules.iter().all(|rule| rule.status != CheckStatus::Failed),
+            rules,
+        }
+    }
+
+    /// Every rule is skipped with `reason` and the policy is not passed
+    pub fn not_applicable(&self, reason: &str) -> PolicyEvaluationDto {
+        PolicyEvaluationDto {
+            policy: self.name.clone(),
+            passed: false,
+            rules: self
+                .rules
+                .iter()
+                .map(|rule| rule.result(CheckStatus::Skipped, Some(reason.to_owned())))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::response::hc1_response_dto::CodeSystem;
+
+    fn code_system(code: &str) -> CodeSystem {
+        CodeSystem {
+            code: Some(code.to_owned()),
+            system: None,
+        }
+    }
+
+    fn ddcc_core_data_set(dose: u8, total_doses: Option<u8>, date: &str) -> DdccCoreDataSet {
+        DdccCoreDataSet {
+            vaccination: Some(Vaccination {
+                date: date.to_owned(),
+                dose,
+                vaccine: code_system("XM68M6"),
+                country: code_system("CHL"),
+                maholder: None,
+                lot: "VC35679".to_owned(),
+                centre: None,
+                brand: code_system("XM8NQ0"),
+                manufacturer: None,
+                valid_from: None,
+                total_doses,
+                practitioner: None,
+                disease: None,
+                next_dose: None,
+                extensions: Default::default(),
+            }),
+            test_result: None,
+            recovery: None,
+            resource_type: None,
+            birth_date: None,
+            name: "Jane Doe".to_owned(),
+            identifier: None,
+            sex: None,
+            certificate: None,
+            extensions: Default::default(),
+        }
+    }
+
+    const POLICY_JSON: &str = r#"{
+        "policies": [{
+            "name": "CHL-entry",
+            "rules": [
+                { "id": "VR-001", "type": "fullSeriesCompleted" },
+                { "id": "VR-002", "type": "minDaysSinceLastDose", "days": 14 },
+                { "id": "VR-003", "type": "maxDaysSinceLastDose", "days": 270 },
+                { "id": "VR-004", "type": "allowedBrandCodes", "codes": ["XM8NQ0", "XM2YG8"] }
+            ]
+        }]
+    }"#;
+
+    const POLICY_TOML: &str = r#"
+        [[policies]]
+        name = "CHL-entry"
+
+        [[policies.rules]]
+        id = "VR-001"
+        type = "fullSeriesCompleted"
+
+        [[policies.rules]]
+        id = "VR-002"
+        type = "minDaysSinceLastDose"
+        days = 14
+
+        [[policies.rules]]
+        id = "VR-003"
+        type = "maxDaysSinceLastDose"
+        days = 270
+
+        [[policies.rules]]
+        id = "VR-004"
+        type = "allowedBrandCodes"
+        codes = ["XM8NQ0", "XM2YG8"]
+    "#;
+
+    #[test]
+    fn policy_file_json_and_toml_test() {
+        let from_json: PolicyFile = serde_json::from_str(POLICY_JSON).unwrap();
+        let from_toml: PolicyFile = toml::from_str(POLICY_TOML).unwrap();
+        assert_eq!(from_json, from_toml);
+        assert_eq!(
+            from_json.policies[0].rules[1].condition,
+            RuleCondition::MinDaysSinceLastDose { days: 14 }
+        );
+    }
+
+    #[test]
+    fn evaluate_policy_test() {
+        let policy = serde_json::from_str::<PolicyFile>(POLICY_JSON)
+            .unwrap()
+            .policies
+            .remove(0);
+        let today = NaiveDate::from_ymd_opt(2023, 10, 20).unwrap();
+
+        let evaluation =
+            policy.evaluate(Some(&ddcc_core_data_set(2, Some(2), "2023-06-01")), today);
+        assert!(evaluation.passed);
+
+        let evaluation = policy.evaluate(
+            Some(&ddcc_core_data_set(1, Some(2), "2023-10-15T10:00:00Z")),
+            today,
+        );
+        assert!(!evaluation.passed);
+        let statuses = evaluation
+            .rules
+            .iter()
+            .map(|rule| rule.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                CheckStatus::Failed,
+                CheckStatus::Failed,
+                CheckStatus::Passed,
+                CheckStatus::Passed
+            ]
+        );
+
+        let evaluation = policy.evaluate(None, today);
+        assert!(!evaluation.passed);
+        assert!(evaluation
+            .rules
+            .iter()
+            .all(|rule| rule.status == CheckStatus::Skipped));
+    }
+}
@@ -1,5 +1,4 @@
 pub mod contract_interface;
-pub mod country_code;
 pub mod data_interface;
 pub mod index;
 pub mod member_data;
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, time::SystemTime};
 
+use lacpass_verifier::country_code::ALPHA3_TO_ALPHA2;
 use log::{debug, info};
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
@@ -15,7 +16,7 @@ use crate::services::{
     },
 };
 
-use super::{country_code::ALPHA3_TO_ALPHA2, member_data::MemberData};
+use super::member_data::MemberData;
 
 pub struct PublicDirectoryWorkerService {
     pub pd_did_member_data_interface_service: PdDidMemberDataInterfaceService,
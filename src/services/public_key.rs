@@ -1,2 +1,3 @@
+pub mod chain_worker_service;
 pub mod data_interface;
 pub mod source_1_worker_service;
@@ -0,0 +1,77 @@
+use lacpass_verifier::x509::X509Utils;
+use log::{debug, info};
+use sea_orm::{DatabaseConnection, TransactionTrait};
+
+use crate::{
+    entities::models::PublicKeyModel,
+    services::{
+        trust_list::data_interface::{KeyChangeType, TrustListVersionService},
+        x509::csca_trust_store::CscaTrustStore,
+    },
+};
+
+use super::data_interface::PublicKeyService;
+
+/// Re-evaluates the CSCA chain of every stored key. Covers keys stored before the trust store
+/// was configured and DSCs or CSCAs whose validity ended since the key was ingested. Keys stored
+/// before JWK thumbprints were computed get theirs on the way
+pub struct ChainWorkerService {
+    public_key_service: PublicKeyService,
+}
+
+impl ChainWorkerService {
+    pub fn new() -> Self {
+        ChainWorkerService {
+            public_key_service: PublicKeyService::new(),
+        }
+    }
+
+    fn get_pem(registry: &PublicKeyModel) -> anyhow::Result<String> {
+        let jwk_str = String::from_utf8(registry.jwk.clone())?;
+        Ok(X509Utils::get_pem_from_string_jwk(&jwk_str)?)
+    }
+
+    pub async fn sweep(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        info!("Starting new sweep operation for the CSCA chain of the public keys");
+        let trust_store = CscaTrustStore::get();
+        for registry in PublicKeyService::find_all_public_keys(db).await? {
+            let pem = match Self::get_pem(&registry) {
+                Ok(v) => v,
+                Err(e) => {
+                    debug!(
+                        "Unable to get the pem of public key {}, error was: {:?}",
+                        registry.id, &e
+                    );
+                    continue;
+                }
+            };
+            let is_chain_trusted = trust_store
+                .validate_dsc(&registry.country_code, &pem)
+                .is_trusted();
+            if is_chain_trusted == registry.is_chain_trusted {
+                continue;
+            }
+            // the key and its trust list change are committed together
+            let txn = db.begin().await?;
+            self.public_key_service
+                .update_public_key(
+                    &txn,
+                    &registry.id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(is_chain_trusted),
+                )
+                .await?;
+            TrustListVersionService::bump_version(&txn, &registry.id, KeyChangeType::Updated)
+                .await?;
+            txn.commit().await?;
+            info!(
+                "CSCA chain of public key {} for country {} re-evaluated: {:?} -> {:?}",
+                registry.id, registry.country_code, registry.is_chain_trusted, is_chain_trusted
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,26 @@
 use crate::databases::pool::Db;
-use crate::dto::response::public_key_response_dto::{PublicKeyCoreResponse, PublicKeyResponseDto};
-use crate::entities::entities::PublicKeyEntity;
+use crate::dto::cache_conditions::CacheConditions;
+use crate::dto::response::public_key_response_dto::{
+    JwksKey, JwksResponseDto, PublicKeyCoreResponse, PublicKeyDetailResponseDto,
+    PublicKeyProvenance, PublicKeyResponseDto,
+};
+use crate::entities::entities::{DidEntity, PublicKeyEntity};
 use crate::entities::models::{PublicKeyActiveModel, PublicKeyModel};
+use crate::entities::public_key::filter::{PublicKeyFilter, PublicKeySort, PublicKeySource};
+use crate::responses::cached_response::CacheValidators;
 use crate::responses::error_message::ErrorMessage;
 use crate::responses::generic_response::Responses;
 use crate::responses::success_messages::SuccessMessage;
-use log::info;
+use crate::services::trust_list::data_interface::TrustListVersionService;
+use lacpass_verifier::types::jwk::Jwk;
+use lacpass_verifier::x509::X509Utils;
+use log::{debug, info};
 use rocket::serde::json::Json;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, PaginatorTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, ModelTrait, PaginatorTrait, Set,
+};
 use sea_orm_rocket::Connection;
+use serde::Serialize;
 use uuid::Uuid;
 pub struct PublicKeyService {}
 
@@ -30,16 +42,47 @@ impl PublicKeyService {
             .await
     }
 
-    /// receives a iso alpha2 country code and returns all associated keys
-    pub async fn find_public_key_by_country(
+    /// receives a iso alpha3 country code and a base64 encoded key identifier and returns the
+    /// matching keys, compromised ones included; keys whose kid is not stored yet are returned too
+    pub async fn find_public_keys_by_country_and_kid(
         db: &DatabaseConnection,
         country_code: &str,
+        kid: &str,
     ) -> Result<Vec<PublicKeyModel>, sea_orm::DbErr> {
-        PublicKeyEntity::find_by_country_code(country_code)
+        PublicKeyEntity::find_by_country_code_and_kid(country_code, kid)
             .all(db)
             .await
     }
 
+    /// returns the keys whose JWK thumbprint matches across every country, compromised ones
+    /// included; used when the certificate does not tell the signer country (e.g. SMART Health
+    /// Cards)
+    pub async fn find_public_keys_by_jwk_thumbprint(
+        db: &DatabaseConnection,
+        jwk_thumbprint: &str,
+    ) -> Result<Vec<PublicKeyModel>, sea_orm::DbErr> {
+        PublicKeyEntity::find_by_jwk_thumbprint(jwk_thumbprint)
+            .all(db)
+            .await
+    }
+
+    /// receives a iso alpha3 country code and returns all associated keys, compromised ones included
+    pub async fn find_all_public_keys_by_country(
+        db: &DatabaseConnection,
+        country_code: &str,
+    ) -> Result<Vec<PublicKeyModel>, sea_orm::DbErr> {
+        PublicKeyEntity::find_all_by_country_code(country_code)
+            .all(db)
+            .await
+    }
+
+    /// every stored key, compromised ones included, ordered by id
+    pub async fn find_all_public_keys(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<PublicKeyModel>, sea_orm::DbErr> {
+        PublicKeyEntity::find_all_ordered().all(db).await
+    }
+
     pub async fn find_public_key_by_content_hash_and_country_code(
         &self,
         db: &DatabaseConnection,
@@ -51,25 +94,30 @@ impl PublicKeyService {
             .await
     }
 
-    pub async fn find_by_id(
+    pub async fn find_by_id<C: ConnectionTrait>(
         &self,
-        db: &DatabaseConnection,
+        db: &C,
         id: &Uuid,
     ) -> Result<Option<PublicKeyModel>, sea_orm::DbErr> {
         PublicKeyEntity::find_by_id(id).one(db).await
     }
 
-    pub async fn insert_public_key(
+    pub async fn insert_public_key<C: ConnectionTrait>(
         &self,
-        db: &DatabaseConnection,
+        db: &C,
         did_id: Option<Uuid>,
         block_number: Option<i64>,
         jwk: Vec<u8>,
         content_hash: &str,
         exp: &u64,
         is_compromised: Option<bool>,
+        compromised_at: Option<u64>,
         country_code: &str,
         url: Option<String>,
+        kid: Option<String>,
+        is_chain_trusted: Option<bool>,
+        certificate_types: Option<String>,
+        jwk_thumbprint: Option<String>,
     ) -> anyhow::Result<PublicKeyModel> {
         let db_registry = PublicKeyActiveModel {
             id: Set(Uuid::new_v4()),
@@ -79,8 +127,13 @@ impl PublicKeyService {
             content_hash: Set(content_hash.to_owned()),
             exp: Set(Some(*exp as i64)),
             is_compromised: Set(is_compromised),
+            compromised_at: Set(compromised_at.map(|v| v as i64)),
             country_code: Set(country_code.to_owned()),
             url: Set(url),
+            kid: Set(kid),
+            is_chain_trusted: Set(is_chain_trusted),
+            certificate_types: Set(certificate_types),
+            jwk_thumbprint: Set(jwk_thumbprint),
         };
         match db_registry.insert(db).await {
             Ok(res) => return Ok(res),
@@ -90,13 +143,15 @@ impl PublicKeyService {
         }
     }
 
-    pub async fn update_public_key(
+    pub async fn update_public_key<C: ConnectionTrait>(
         &self,
-        db: &DatabaseConnection,
+        db: &C,
         public_key_id: &Uuid,
         block_number: Option<u64>,
         exp: Option<u64>,
         is_compromised: Option<bool>,
+        compromised_at: Option<u64>,
+        is_chain_trusted: Option<Option<bool>>,
     ) -> anyhow::Result<PublicKeyModel> {
         match self.find_by_id(db, public_key_id).await {
             Ok(v) => match v {
@@ -120,6 +175,18 @@ impl PublicKeyService {
                         }
                         None => {}
                     }
+                    match compromised_at {
+                        Some(v) => {
+                            s.compromised_at = Set(Some(v as i64));
+                        }
+                        None => {}
+                    }
+                    match is_chain_trusted {
+                        Some(v) => {
+                            s.is_chain_trusted = Set(v);
+                        }
+                        None => {}
+                    }
                     match s.update(db).await {
                         Ok(res) => return Ok(res),
                         Err(err) => {
@@ -144,6 +211,9 @@ impl PublicKeyService {
         page_size: Option<u64>,
         public_directory_contract_address: &str,
         chain_id: &str,
+        filter: &PublicKeyFilter,
+        sort: PublicKeySort,
+        conditions: &CacheConditions,
     ) -> Responses<Json<SuccessMessage<PublicKeyResponseDto>>, Json<ErrorMessage<'static>>> {
         let db = connection.into_inner();
         let page = page.unwrap_or(1);
@@ -160,9 +230,15 @@ impl PublicKeyService {
             }
             _ => {}
         }
-        let paginator =
-            PublicKeyEntity::find_by_public_directory(public_directory_contract_address, chain_id)
-                .paginate(db, page_size);
+        let paginator = PublicKeyEntity::sorted(
+            PublicKeyEntity::find_by_public_directory(
+                public_directory_contract_address,
+                chain_id,
+                filter,
+            ),
+            sort,
+        )
+        .paginate(db, page_size);
         let num_pages;
         match paginator.num_pages().await {
             Ok(r) => {
@@ -210,15 +286,23 @@ impl PublicKeyService {
 
         match result {
             Ok(keys) => {
-                return Responses::Sucess(Json::from(SuccessMessage {
-                    data: PublicKeyResponseDto {
-                        page,
-                        results_per_page: page_size,
-                        num_pages,
-                        keys,
-                    },
-                    trace_id: trace_id.to_string(),
-                }));
+                let data = PublicKeyResponseDto {
+                    page,
+                    results_per_page: page_size,
+                    num_pages,
+                    keys,
+                };
+                // expiration is relative to the request time, a version time does not date it
+                let validators =
+                    Self::get_cache_validators(db, &data, filter.not_expired_at.is_none()).await;
+                return Responses::cached(
+                    Json::from(SuccessMessage {
+                        data,
+                        trace_id: trace_id.to_string(),
+                    }),
+                    validators,
+                    conditions,
+                );
             }
             Err(e) => {
                 error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
@@ -229,4 +313,261 @@ impl PublicKeyService {
             }
         };
     }
+
+    /// Trusted keys of the public directory as JWK Set members, ordered by id. Compromised keys
+    /// and keys whose certificate does not chain to a trusted CSCA are left out
+    pub async fn find_jwks_keys(
+        db: &DatabaseConnection,
+        public_directory_contract_address: &str,
+        chain_id: &str,
+    ) -> Result<Vec<JwksKey>, sea_orm::DbErr> {
+        let registries = PublicKeyEntity::find_trusted_by_public_directory(
+            public_directory_contract_address,
+            chain_id,
+        )
+        .all(db)
+        .await?;
+        Ok(Self::to_jwks_keys(registries))
+    }
+
+    /// Keys of the public directory among `ids`, compromised and untrusted ones included
+    pub async fn find_public_keys_by_ids(
+        db: &DatabaseConnection,
+        public_directory_contract_address: &str,
+        chain_id: &str,
+        ids: &[Uuid],
+    ) -> Result<Vec<PublicKeyModel>, sea_orm::DbErr> {
+        PublicKeyEntity::find_by_public_directory_and_ids(
+            public_directory_contract_address,
+            chain_id,
+            ids,
+        )
+        .all(db)
+        .await
+    }
+
+    /// Same as `find_public_keys_by_ids` but only the keys `find_jwks_keys` would return
+    pub async fn find_trusted_public_keys_by_ids(
+        db: &DatabaseConnection,
+        public_directory_contract_address: &str,
+        chain_id: &str,
+        ids: &[Uuid],
+    ) -> Result<Vec<PublicKeyModel>, sea_orm::DbErr> {
+        PublicKeyEntity::find_trusted_by_public_directory_and_ids(
+            public_directory_contract_address,
+            chain_id,
+            ids,
+        )
+        .all(db)
+        .await
+    }
+
+    /// Returns the trusted keys of the exposed public directory as a JWK Set, unpaginated
+    pub async fn get_jwks_from_lacchain(
+        connection: Connection<'_, Db>,
+        public_directory_contract_address: &str,
+        chain_id: &str,
+        conditions: &CacheConditions,
+    ) -> Responses<Json<JwksResponseDto>, Json<ErrorMessage<'static>>> {
+        let db = connection.into_inner();
+        let trace_id: Uuid = Uuid::new_v4();
+        match Self::find_jwks_keys(db, public_directory_contract_address, chain_id).await {
+            Ok(keys) => {
+                let data = JwksResponseDto { keys };
+                let validators = Self::get_cache_validators(db, &data, true).await;
+                Responses::cached(Json::from(data), validators, conditions)
+            }
+            Err(e) => {
+                error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
+                Responses::BadRequest(Json::from(ErrorMessage {
+                    message: "Internal error when retrieving public keys",
+                    trace_id: trace_id.to_string(),
+                }))
+            }
+        }
+    }
+
+    /// Validators of a listing: the content tag and, when `versioned` (the listing only changes
+    /// along with the trust list version), the time of the current trust list version. `None`
+    /// (no caching) when they cannot be computed
+    pub async fn get_cache_validators<S: Serialize>(
+        db: &DatabaseConnection,
+        data: &S,
+        versioned: bool,
+    ) -> Option<CacheValidators> {
+        let last_modified = match versioned {
+            true => match TrustListVersionService::get_last_modified(db).await {
+                Ok(v) => v,
+                Err(e) => {
+                    debug!("Unable to get the trust list version time: {}", &e);
+                    return None;
+                }
+            },
+            false => None,
+        };
+        match CacheValidators::from_content(data, last_modified) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                debug!("Unable to compute the cache validators: {:?}", &e);
+                None
+            }
+        }
+    }
+
+    /// Keys registered with `kid`, from the public directory members first and then from the
+    /// external source, along with where they come from
+    pub async fn find_public_key_details_by_kid(
+        db: &DatabaseConnection,
+        public_directory_contract_address: &str,
+        chain_id: &str,
+        kid: &str,
+    ) -> Result<Vec<PublicKeyDetailResponseDto>, sea_orm::DbErr> {
+        let mut details = vec![];
+        for source in [PublicKeySource::Lacchain, PublicKeySource::ExternalSource1] {
+            let filter = PublicKeyFilter {
+                source,
+                kid: Some(kid.to_owned()),
+                ..Default::default()
+            };
+            let registries = PublicKeyEntity::sorted(
+                PublicKeyEntity::find_by_public_directory(
+                    public_directory_contract_address,
+                    chain_id,
+                    &filter,
+                ),
+                PublicKeySort::Id,
+            )
+            .all(db)
+            .await?;
+            for registry in registries {
+                let did = match registry.did_id {
+                    Some(_) => registry
+                        .find_related(DidEntity)
+                        .one(db)
+                        .await?
+                        .map(|did| did.did),
+                    None => None,
+                };
+                let provenance = PublicKeyProvenance {
+                    source,
+                    did,
+                    block_number: registry.block_number,
+                    url: registry.url.clone(),
+                    content_hash: registry.content_hash.clone(),
+                    exp: registry.exp,
+                    is_compromised: registry.is_compromised.unwrap_or(false),
+                    compromised_at: registry.compromised_at,
+                    is_chain_trusted: registry.is_chain_trusted,
+                    certificate_types: registry.certificate_types.clone(),
+                };
+                let id = registry.id;
+                match Self::to_jwks_key(registry) {
+                    Ok(key) => details.push(PublicKeyDetailResponseDto { key, provenance }),
+                    Err(e) => debug!("Unable to build the jwk of public key {}: {}", id, &e),
+                }
+            }
+        }
+        Ok(details)
+    }
+
+    /// Returns the keys registered with `kid` and their provenance
+    pub async fn get_by_kid_from_lacchain(
+        connection: Connection<'_, Db>,
+        kid: &str,
+        public_directory_contract_address: &str,
+        chain_id: &str,
+    ) -> Responses<Json<SuccessMessage<Vec<PublicKeyDetailResponseDto>>>, Json<ErrorMessage<'static>>>
+    {
+        let db = connection.into_inner();
+        let trace_id: Uuid = Uuid::new_v4();
+        let kid = Self::normalize_kid(kid);
+        match Self::find_public_key_details_by_kid(
+            db,
+            public_directory_contract_address,
+            chain_id,
+            &kid,
+        )
+        .await
+        {
+            Ok(keys) if keys.is_empty() => {
+                let message = "No public key found for the kid";
+                debug!("TRACE_ID: {}, DESCRIPTION: {} {}", trace_id, message, kid);
+                Responses::NotFound(Json::from(ErrorMessage {
+                    message,
+                    trace_id: trace_id.to_string(),
+                }))
+            }
+            Ok(keys) => Responses::Sucess(Json::from(SuccessMessage {
+                data: keys,
+                trace_id: trace_id.to_string(),
+            })),
+            Err(e) => {
+                error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
+                Responses::BadRequest(Json::from(ErrorMessage {
+                    message: "Internal error when retrieving public keys",
+                    trace_id: trace_id.to_string(),
+                }))
+            }
+        }
+    }
+
+    /// Kids are stored in base64; the base64url form (e.g. unescaped in a path) is accepted too
+    pub fn normalize_kid(kid: &str) -> String {
+        let mut kid = kid.replace('-', "+").replace('_', "/");
+        while kid.len() % 4 != 0 {
+            kid.push('=');
+        }
+        kid
+    }
+
+    /// Skips (and logs) the keys whose stored jwk cannot be exported
+    pub fn to_jwks_keys(registries: Vec<PublicKeyModel>) -> Vec<JwksKey> {
+        registries
+            .into_iter()
+            .filter_map(|registry| {
+                let id = registry.id;
+                match Self::to_jwks_key(registry) {
+                    Ok(key) => Some(key),
+                    Err(e) => {
+                        debug!("Unable to build the jwk of public key {}: {}", id, &e);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Keys stored before kids were computed get their kid derived from the certificate
+    fn to_jwks_key(registry: PublicKeyModel) -> anyhow::Result<JwksKey> {
+        let jwk_str = String::from_utf8(registry.jwk)?;
+        let mut jwk: Jwk = serde_json::from_str(&jwk_str)?;
+        let pem = X509Utils::format_pem(X509Utils::get_pem_from_string_jwk(&jwk_str)?);
+        jwk.kid = match registry.kid {
+            Some(kid) => Some(kid),
+            None => Some(X509Utils::get_kid_string_from_pem(pem.clone())?),
+        };
+        Ok(JwksKey {
+            jwk,
+            x5t_s256: X509Utils::get_x5t_s256_from_pem(pem)?,
+            country: registry.country_code,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_kid_test() {
+        assert_eq!(
+            PublicKeyService::normalize_kid("2Rk3X8HntrI="),
+            "2Rk3X8HntrI="
+        );
+        assert_eq!(
+            PublicKeyService::normalize_kid("2Rk3X8HntrI"),
+            "2Rk3X8HntrI="
+        );
+        assert_eq!(PublicKeyService::normalize_kid("a-b_cw"), "a+b/cw==");
+    }
 }
@@ -1,10 +1,17 @@
 use crypto::{digest::Digest, sha3::Sha3};
+use lacpass_verifier::{
+    country_code,
+    x509::{DccCertificateType, X509Utils},
+};
 use log::{debug, info};
 use reqwest::Client;
-use sea_orm::DatabaseConnection;
+use sea_orm::{DatabaseConnection, TransactionTrait};
 use serde::{Deserialize, Serialize};
 
-use crate::services::{public_directory::country_code, x509::x509_utils::X509Utils};
+use crate::services::{
+    trust_list::data_interface::{KeyChangeType, TrustListVersionService},
+    x509::csca_trust_store::{CscaTrustStore, DscChainStatus},
+};
 
 use super::data_interface::PublicKeyService;
 
@@ -40,6 +47,10 @@ impl ExternalSource1WorkerService {
         valid_to: u64,
         country_code: String,
         url: Option<String>,
+        kid: Option<String>,
+        is_chain_trusted: Option<bool>,
+        certificate_types: Option<String>,
+        jwk_thumbprint: Option<String>,
     ) -> anyhow::Result<()> {
         match self
             .public_key_service
@@ -54,22 +65,36 @@ impl ExternalSource1WorkerService {
                     Ok(())
                 }
                 None => {
+                    // the key and its trust list change are committed together
+                    let txn = db.begin().await?;
                     match self
                         .public_key_service
                         .insert_public_key(
-                            db,
+                            &txn,
                             None,
                             None,
                             jwk_bytes,
                             &content_hash,
                             &valid_to,
                             None,
+                            None,
                             &country_code,
                             url,
+                            kid,
+                            is_chain_trusted,
+                            certificate_types,
+                            jwk_thumbprint,
                         )
                         .await
                     {
-                        Ok(_) => {
+                        Ok(inserted) => {
+                            TrustListVersionService::bump_version(
+                                &txn,
+                                &inserted.id,
+                                KeyChangeType::Added,
+                            )
+                            .await?;
+                            txn.commit().await?;
                             info!("Inserted new public key for country: {}", country_code);
                             Ok(())
                         }
@@ -133,8 +158,17 @@ impl ExternalSource1WorkerService {
                                                 }
                                                 let jwk_bytes = jwk_string.as_bytes();
 
+                                                let chain_status = CscaTrustStore::get().validate_dsc(&alpha3_country_code, pem_candidate);
+                                                if let DscChainStatus::Untrusted(e) = &chain_status {
+                                                    info!("Flagging public key for country {}: {}", alpha3_country_code, e);
+                                                }
+                                                let certificate_types = X509Utils::get_dcc_certificate_types_from_pem(pem_candidate.to_string())
+                                                    .ok()
+                                                    .flatten()
+                                                    .map(|types| DccCertificateType::to_codes(&types));
+                                                let jwk_thumbprint = X509Utils::get_jwk_thumbprint_from_pem(pem_candidate.to_string()).ok();
                                                 match X509Utils::get_expiration_from_pem(pem_candidate.to_string()) {
-                                                    Ok(expiration) => Some((content_hash, jwk_bytes.to_owned(), expiration, alpha3_country_code, Some(key.url.clone()) )),
+                                                    Ok(expiration) => Some((content_hash, jwk_bytes.to_owned(), expiration, alpha3_country_code, Some(key.url.clone()), jwk.kid.clone(), chain_status.is_trusted(), certificate_types, jwk_thumbprint)),
                                                     Err(e) => {
                                                         let message = format!(
                                                             "Error while getting 'Expiration' from pem - for country {:?}; error was: {:?}",
@@ -178,6 +212,10 @@ impl ExternalSource1WorkerService {
                         let valid_to = candidate.2;
                         let country_code = candidate.3;
                         let url = candidate.4;
+                        let kid = candidate.5;
+                        let is_chain_trusted = candidate.6;
+                        let certificate_types = candidate.7;
+                        let jwk_thumbprint = candidate.8;
                         match self
                             .update_or_insert_public_key(
                                 db,
@@ -186,6 +224,10 @@ impl ExternalSource1WorkerService {
                                 valid_to,
                                 country_code.clone(),
                                 url,
+                                kid,
+                                is_chain_trusted,
+                                certificate_types,
+                                jwk_thumbprint,
                             )
                             .await
                         {
@@ -0,0 +1,305 @@
+use chrono::Utc;
+use clap::__macro_refs::once_cell::sync::OnceCell;
+use log::{debug, error};
+use openssl::sha::sha256;
+use rocket::serde::json::Json;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::{
+    dto::response::{
+        hc1_response_dto::{HC1ValidationResponseDto, VerificationCheck},
+        receipt_response_dto::VerificationReceiptClaims,
+        shc_response_dto::ShcValidationResponseDto,
+        signing_key_response_dto::SigningKeyResponseDto,
+        vds_nc_response_dto::VdsNcValidationResponseDto,
+        verify_batch_response_dto::VerifyBatchResponseDto,
+    },
+    responses::{
+        error_message::ErrorMessage, generic_response::Responses, success_messages::SuccessMessage,
+    },
+    services::{
+        signing::jws_signer::JwsSigner, trust_list::data_interface::TrustListVersionService,
+    },
+};
+
+const RECEIPT_TYP: &str = "verification-receipt+jwt";
+
+/// signer loaded once from `RECEIPT_PRIVATE_KEY_FILE`; `None` when receipts are not configured
+static RECEIPT_SIGNER: OnceCell<Option<JwsSigner>> = OnceCell::new();
+
+/// Verification results a receipt can be issued for
+pub trait ReceiptSubject {
+    fn is_valid(&self) -> bool;
+    fn kid(&self) -> Option<String>;
+    fn checks(&self) -> Vec<VerificationCheck>;
+    fn set_receipt(&mut self, receipt: String);
+}
+
+impl ReceiptSubject for HC1ValidationResponseDto {
+    fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+    fn kid(&self) -> Option<String> {
+        self.kid.clone()
+    }
+    fn checks(&self) -> Vec<VerificationCheck> {
+        self.checks.clone()
+    }
+    fn set_receipt(&mut self, receipt: String) {
+        self.receipt = Some(receipt);
+    }
+}
+
+impl ReceiptSubject for ShcValidationResponseDto {
+    fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+    fn kid(&self) -> Option<String> {
+        self.kid.clone()
+    }
+    fn checks(&self) -> Vec<VerificationCheck> {
+        self.checks.clone()
+    }
+    fn set_receipt(&mut self, receipt: String) {
+        self.receipt = Some(receipt);
+    }
+}
+
+impl ReceiptSubject for VdsNcValidationResponseDto {
+    fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+    fn kid(&self) -> Option<String> {
+        self.kid.clone()
+    }
+    fn checks(&self) -> Vec<VerificationCheck> {
+        self.checks.clone()
+    }
+    fn set_receipt(&mut self, receipt: String) {
+        self.receipt = Some(receipt);
+    }
+}
+
+pub fn get_receipt_signer() -> Option<&'static JwsSigner> {
+    RECEIPT_SIGNER
+        .get_or_init(|| JwsSigner::load("RECEIPT_PRIVATE_KEY_FILE", "receipt"))
+        .as_ref()
+}
+
+pub fn get_receipt_claims(
+    input: &[u8],
+    result: &impl ReceiptSubject,
+    trust_list_version: i64,
+    jti: String,
+    iat: i64,
+) -> VerificationReceiptClaims {
+    VerificationReceiptClaims {
+        jti,
+        iat,
+        input_sha256: hex::encode(sha256(input)),
+        is_valid: result.is_valid(),
+        checks: result.checks(),
+        kid: result.kid(),
+        trust_list_version,
+    }
+}
+
+fn bad_request<T>(
+    message: &'static str,
+    trace_id: String,
+) -> Responses<T, Json<ErrorMessage<'static>>> {
+    Responses::BadRequest(Json::from(ErrorMessage { message, trace_id }))
+}
+
+/// Signer and trust list version needed to issue receipts, or the error response to return
+async fn get_receipt_context<T>(
+    db: &DatabaseConnection,
+    trace_id: &str,
+) -> Result<(&'static JwsSigner, i64), Responses<T, Json<ErrorMessage<'static>>>> {
+    let signer = match get_receipt_signer() {
+        Some(v) => v,
+        None => {
+            return Err(bad_request(
+                "Verification receipts are not configured",
+                trace_id.to_owned(),
+            ))
+        }
+    };
+    match TrustListVersionService::get_current_version(db).await {
+        Ok(trust_list_version) => Ok((signer, trust_list_version)),
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, e);
+            Err(bad_request(
+                "Unable to issue the verification receipt",
+                trace_id.to_owned(),
+            ))
+        }
+    }
+}
+
+/// Adds a signed receipt of `input` verification to a successful response when requested
+pub async fn with_receipt<T: ReceiptSubject>(
+    db: &DatabaseConnection,
+    response: Responses<Json<SuccessMessage<T>>, Json<ErrorMessage<'static>>>,
+    input: &[u8],
+    receipt_requested: bool,
+) -> Responses<Json<SuccessMessage<T>>, Json<ErrorMessage<'static>>> {
+    let mut result = match response {
+        Responses::Sucess(result) if receipt_requested => result.into_inner(),
+        response => return response,
+    };
+    let (signer, trust_list_version) = match get_receipt_context(db, &result.trace_id).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let claims = get_receipt_claims(
+        input,
+        &result.data,
+        trust_list_version,
+        result.trace_id.clone(),
+        Utc::now().timestamp(),
+    );
+    match signer.sign(RECEIPT_TYP, &claims) {
+        Ok(receipt) => {
+            debug!("TRACE_ID: {}, receipt issued", result.trace_id);
+            result.data.set_receipt(receipt);
+            Responses::Sucess(Json::from(result))
+        }
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {:?}", result.trace_id, e);
+            bad_request("Unable to issue the verification receipt", result.trace_id)
+        }
+    }
+}
+
+/// Same as `with_receipt` for every verified item of a batch, `inputs` being the items `data`
+/// in the request order. Each receipt `jti` is the batch trace id followed by the item index
+pub async fn with_batch_receipts(
+    db: &DatabaseConnection,
+    response: Responses<Json<SuccessMessage<VerifyBatchResponseDto>>, Json<ErrorMessage<'static>>>,
+    inputs: Vec<String>,
+    receipt_requested: bool,
+) -> Responses<Json<SuccessMessage<VerifyBatchResponseDto>>, Json<ErrorMessage<'static>>> {
+    let mut result = match response {
+        Responses::Sucess(result) if receipt_requested => result.into_inner(),
+        response => return response,
+    };
+    let (signer, trust_list_version) = match get_receipt_context(db, &result.trace_id).await {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let iat = Utc::now().timestamp();
+    for (index, (item, input)) in result
+        .data
+        .results
+        .iter_mut()
+        .zip(inputs.iter())
+        .enumerate()
+    {
+        if let Some(item_result) = item.result.as_mut() {
+            let claims = get_receipt_claims(
+                input.as_bytes(),
+                &*item_result,
+                trust_list_version,
+                format!("{}.{}", result.trace_id, index),
+                iat,
+            );
+            match signer.sign(RECEIPT_TYP, &claims) {
+                Ok(receipt) => item_result.set_receipt(receipt),
+                Err(e) => {
+                    error!("TRACE_ID: {}, DESCRIPTION: {:?}", result.trace_id, e);
+                    return bad_request(
+                        "Unable to issue the verification receipt",
+                        result.trace_id,
+                    );
+                }
+            }
+        }
+    }
+    Responses::Sucess(Json::from(result))
+}
+
+/// Public key third parties use to check receipts offline
+pub fn get_receipt_public_key(
+) -> Responses<Json<SuccessMessage<SigningKeyResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id = Uuid::new_v4().to_string();
+    let signer = match get_receipt_signer() {
+        Some(v) => v,
+        None => return bad_request("Verification receipts are not configured", trace_id),
+    };
+    match signer.get_public_key() {
+        Ok(data) => Responses::Sucess(Json::from(SuccessMessage { data, trace_id })),
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, e);
+            bad_request("Unable to get the receipt public key", trace_id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        nid::Nid,
+    };
+
+    use crate::{
+        dto::response::hc1_response_dto::{CheckCode, CheckStatus, CheckType},
+        services::signing::jws::{decode_jws, verify_es256_signature},
+    };
+
+    use super::*;
+
+    fn signer() -> JwsSigner {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        JwsSigner::new(&key.private_key_to_pem().unwrap()).unwrap()
+    }
+
+    fn validation_response() -> HC1ValidationResponseDto {
+        HC1ValidationResponseDto {
+            is_valid: true,
+            kid: Some("a2lk".to_owned()),
+            checks: vec![VerificationCheck {
+                check: CheckType::Signature,
+                status: CheckStatus::Passed,
+                code: CheckCode::Ok,
+                message: None,
+                kid: Some("a2lk".to_owned()),
+                country: Some("CHL".to_owned()),
+            }],
+            payload_type: None,
+            ddcc_core_data_set: None,
+            eu_dcc: None,
+            policy: None,
+            receipt: None,
+        }
+    }
+
+    #[test]
+    fn sign_receipt_test() {
+        let signer = signer();
+        let claims = get_receipt_claims(
+            b"HC1:...",
+            &validation_response(),
+            7,
+            "trace".to_owned(),
+            1700000000,
+        );
+        let receipt = signer.sign(RECEIPT_TYP, &claims).unwrap();
+        let jws = decode_jws(&receipt).unwrap();
+        assert_eq!(jws.header.kid.as_deref(), Some(signer.get_kid()));
+        let jwk = signer.get_public_key().unwrap().jwk;
+        assert!(
+            verify_es256_signature(&jwk, jws.signing_input.as_bytes(), &jws.signature).unwrap()
+        );
+        let decoded: VerificationReceiptClaims = serde_json::from_slice(&jws.payload).unwrap();
+        assert_eq!(decoded.input_sha256, hex::encode(sha256(b"HC1:...")));
+        assert_eq!(decoded.kid.as_deref(), Some("a2lk"));
+        assert_eq!(decoded.trust_list_version, 7);
+        assert_eq!(decoded.jti, "trace");
+        assert_eq!(decoded.checks.len(), 1);
+        assert!(decoded.is_valid);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod jws;
+pub mod jws_signer;
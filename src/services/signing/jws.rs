@@ -0,0 +1,172 @@
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+use log::debug;
+use openssl::{
+    bn::BigNum,
+    ec::{EcGroup, EcKey},
+    ecdsa::EcdsaSig,
+    nid::Nid,
+    sha::sha256,
+};
+use serde::Deserialize;
+
+use crate::{
+    dto::response::{
+        hc1_response_dto::{CheckCode, CheckType, VerificationCheck},
+        public_key_response_dto::Jwk,
+    },
+    services::signing::jws_signer::JWS_ALG,
+};
+
+pub const ES256_COORDINATE_LENGTH: usize = 32;
+/// `zip` header value of payloads compressed with raw DEFLATE
+const DEFLATE_ZIP: &str = "DEF";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwsHeader {
+    pub alg: String,
+    pub zip: Option<String>,
+    pub kid: Option<String>,
+}
+
+/// A compact JWS split into its parts
+#[derive(Debug, Clone)]
+pub struct CompactJws {
+    pub header: JwsHeader,
+    /// `<header>.<payload>` as found in the JWS, which is what gets signed
+    pub signing_input: String,
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Splits a compact JWS and inflates its payload when `zip` is `DEF`
+pub fn decode_jws(jws: &str) -> Result<CompactJws, VerificationCheck> {
+    let failed = |message: &str| {
+        VerificationCheck::failed(CheckType::Decoding, CheckCode::InvalidJws, message)
+    };
+    let parts = jws.split('.').collect::<Vec<_>>();
+    if parts.len() != 3 {
+        return Err(failed("JWS must have three parts"));
+    }
+    let header: JwsHeader = match base64_url::decode(parts[0])
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    {
+        Some(v) => v,
+        None => return Err(failed("Invalid JWS header")),
+    };
+    if header.alg != JWS_ALG {
+        return Err(VerificationCheck::failed(
+            CheckType::Decoding,
+            CheckCode::UnsupportedAlgorithm,
+            &format!("Unsupported algorithm: {}", header.alg),
+        ));
+    }
+    let raw_payload = match base64_url::decode(parts[1]) {
+        Ok(v) => v,
+        Err(e) => return Err(failed(&format!("Invalid JWS payload: {}", e))),
+    };
+    let payload = match header.zip.as_deref() {
+        Some(DEFLATE_ZIP) => {
+            let mut inflated = Vec::new();
+            if let Err(e) = DeflateDecoder::new(raw_payload.as_slice()).read_to_end(&mut inflated) {
+                return Err(VerificationCheck::failed(
+                    CheckType::Decoding,
+                    CheckCode::InvalidCompression,
+                    &format!("Invalid DEFLATE compressed payload: {}", e),
+                ));
+            }
+            inflated
+        }
+        _ => raw_payload,
+    };
+    let signature = match base64_url::decode(parts[2]) {
+        Ok(v) => v,
+        Err(e) => return Err(failed(&format!("Invalid JWS signature: {}", e))),
+    };
+    Ok(CompactJws {
+        header,
+        signing_input: format!("{}.{}", parts[0], parts[1]),
+        payload,
+        signature,
+    })
+}
+
+/// Verifies a JWS ES256 signature (`r || s`, 64 bytes) with an EC P-256 jwk
+pub fn verify_es256_signature(
+    jwk: &Jwk,
+    signing_input: &[u8],
+    signature: &[u8],
+) -> anyhow::Result<bool> {
+    if signature.len() != 2 * ES256_COORDINATE_LENGTH {
+        return Ok(false);
+    }
+    let (x, y) = match (&jwk.x, &jwk.y) {
+        (Some(x), Some(y)) => (base64_url::decode(x)?, base64_url::decode(y)?),
+        _ => {
+            let message = "Missing 'x' or 'y' coordinates in jwk";
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    };
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = EcKey::from_public_key_affine_coordinates(
+        &group,
+        &BigNum::from_slice(&x)?,
+        &BigNum::from_slice(&y)?,
+    )?;
+    let r = BigNum::from_slice(&signature[..ES256_COORDINATE_LENGTH])?;
+    let s = BigNum::from_slice(&signature[ES256_COORDINATE_LENGTH..])?;
+    let ecdsa_sig = EcdsaSig::from_private_components(r, s)?;
+    Ok(ecdsa_sig.verify(&sha256(signing_input), &key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::bn::BigNumContext;
+
+    #[test]
+    fn verify_es256_signature_test() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let mut x = BigNum::new().unwrap();
+        let mut y = BigNum::new().unwrap();
+        key.public_key()
+            .affine_coordinates_gfp(&group, &mut x, &mut y, &mut ctx)
+            .unwrap();
+        let jwk = Jwk {
+            alg: None,
+            r#use: None,
+            kty: Some("EC".to_owned()),
+            kid: None,
+            x5c: None,
+            x5t: None,
+            n: None,
+            e: None,
+            x: Some(base64_url::encode(
+                &x.to_vec_padded(ES256_COORDINATE_LENGTH as i32).unwrap(),
+            )),
+            y: Some(base64_url::encode(
+                &y.to_vec_padded(ES256_COORDINATE_LENGTH as i32).unwrap(),
+            )),
+            crv: Some("P-256".to_owned()),
+        };
+        let signing_input = b"eyJhbGciOiJFUzI1NiJ9.e30";
+        let ecdsa_sig = EcdsaSig::sign(&sha256(signing_input), &key).unwrap();
+        let mut signature = ecdsa_sig
+            .r()
+            .to_vec_padded(ES256_COORDINATE_LENGTH as i32)
+            .unwrap();
+        signature.extend(
+            ecdsa_sig
+                .s()
+                .to_vec_padded(ES256_COORDINATE_LENGTH as i32)
+                .unwrap(),
+        );
+        assert!(verify_es256_signature(&jwk, signing_input, &signature).unwrap());
+        assert!(!verify_es256_signature(&jwk, b"eyJhbGciOiJFUzI1NiJ9.e31", &signature).unwrap());
+    }
+}
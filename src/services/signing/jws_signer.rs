@@ -0,0 +1,167 @@
+use std::fs;
+
+use lacpass_verifier::x509::X509Utils;
+use log::{error, info};
+use openssl::{
+    bn::{BigNum, BigNumContext},
+    ec::EcKey,
+    ecdsa::EcdsaSig,
+    nid::Nid,
+    pkey::{PKey, Private},
+    sha::sha256,
+};
+use rocket::serde::Serialize;
+
+use crate::{
+    dto::response::{
+        public_key_response_dto::Jwk, signing_key_response_dto::SigningKeyResponseDto,
+    },
+    services::signing::jws::ES256_COORDINATE_LENGTH,
+    utils::utils::Utils,
+};
+
+pub const JWS_ALG: &str = "ES256";
+
+/// Signs documents issued by the service (e.g. verification receipts) as compact ES256 JWS
+/// with one of the service own P-256 keys
+pub struct JwsSigner {
+    private_key: EcKey<Private>,
+    /// public key, its `kid` is the JWK thumbprint
+    jwk: Jwk,
+}
+
+impl JwsSigner {
+    pub fn new(private_key_pem: &[u8]) -> anyhow::Result<Self> {
+        let private_key = PKey::private_key_from_pem(private_key_pem)?.ec_key()?;
+        if private_key.group().curve_name() != Some(Nid::X9_62_PRIME256V1) {
+            return Err(anyhow::anyhow!("Signing key must be an EC P-256 key"));
+        }
+        private_key.check_key()?;
+        let mut ctx = BigNumContext::new()?;
+        let mut x = BigNum::new()?;
+        let mut y = BigNum::new()?;
+        private_key.public_key().affine_coordinates_gfp(
+            private_key.group(),
+            &mut x,
+            &mut y,
+            &mut ctx,
+        )?;
+        let mut jwk = Jwk {
+            alg: Some(JWS_ALG.to_owned()),
+            r#use: Some("sig".to_owned()),
+            kty: Some("EC".to_owned()),
+            kid: None,
+            x5c: None,
+            x5t: None,
+            n: None,
+            e: None,
+            x: Some(base64_url::encode(
+                &x.to_vec_padded(ES256_COORDINATE_LENGTH as i32)?,
+            )),
+            y: Some(base64_url::encode(
+                &y.to_vec_padded(ES256_COORDINATE_LENGTH as i32)?,
+            )),
+            crv: Some("P-256".to_owned()),
+        };
+        jwk.kid = Some(X509Utils::get_jwk_thumbprint(&jwk)?);
+        Ok(JwsSigner { private_key, jwk })
+    }
+
+    /// Loads the signer from the PKCS#8 or SEC1 pem in the file pointed by `env_var`
+    pub fn from_env(env_var: &str) -> anyhow::Result<Self> {
+        let path = Utils::get_env_or_err(env_var).map_err(|e| anyhow::anyhow!(e))?;
+        Self::new(fs::read_to_string(path)?.as_bytes())
+    }
+
+    /// Same as `from_env`, `None` when `env_var` is not set or the key can't be loaded;
+    /// `purpose` only names the key in the logs
+    pub fn load(env_var: &str, purpose: &str) -> Option<Self> {
+        if Utils::get_env_or_err(env_var).is_err() {
+            return None;
+        }
+        match Self::from_env(env_var) {
+            Ok(v) => {
+                info!("Loaded {} signing key {}", purpose, v.get_kid());
+                Some(v)
+            }
+            Err(e) => {
+                error!("Unable to load the {} signing key: {:?}", purpose, e);
+                None
+            }
+        }
+    }
+
+    pub fn get_kid(&self) -> &str {
+        self.jwk.kid.as_deref().unwrap_or_default()
+    }
+
+    pub fn get_public_key(&self) -> anyhow::Result<SigningKeyResponseDto> {
+        let public_key =
+            EcKey::from_public_key(self.private_key.group(), self.private_key.public_key())?;
+        Ok(SigningKeyResponseDto {
+            alg: JWS_ALG.to_owned(),
+            jwk: self.jwk.clone(),
+            pem: String::from_utf8(PKey::from_ec_key(public_key)?.public_key_to_pem()?)?,
+        })
+    }
+
+    /// Compact JWS (`<header>.<claims>.<r || s>`) of the claims, `typ` being set in the header
+    pub fn sign<T: Serialize>(&self, typ: &str, claims: &T) -> anyhow::Result<String> {
+        let header = serde_json::json!({
+            "alg": JWS_ALG,
+            "typ": typ,
+            "kid": self.get_kid(),
+        });
+        let signing_input = format!(
+            "{}.{}",
+            base64_url::encode(&serde_json::to_vec(&header)?),
+            base64_url::encode(&serde_json::to_vec(claims)?)
+        );
+        let ecdsa_sig = EcdsaSig::sign(&sha256(signing_input.as_bytes()), &self.private_key)?;
+        let mut signature = ecdsa_sig
+            .r()
+            .to_vec_padded(ES256_COORDINATE_LENGTH as i32)?;
+        signature.extend(
+            ecdsa_sig
+                .s()
+                .to_vec_padded(ES256_COORDINATE_LENGTH as i32)?,
+        );
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            base64_url::encode(&signature)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::ec::EcGroup;
+
+    use crate::services::signing::jws::{decode_jws, verify_es256_signature};
+
+    use super::*;
+
+    #[test]
+    fn sign_test() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let signer = JwsSigner::new(&key.private_key_to_pem().unwrap()).unwrap();
+        let jws = signer
+            .sign("test+jwt", &serde_json::json!({ "a": 1 }))
+            .unwrap();
+        let jws = decode_jws(&jws).unwrap();
+        assert_eq!(jws.header.kid.as_deref(), Some(signer.get_kid()));
+        let jwk = signer.get_public_key().unwrap().jwk;
+        assert!(
+            verify_es256_signature(&jwk, jws.signing_input.as_bytes(), &jws.signature).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_other_curves_test() {
+        let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        assert!(JwsSigner::new(&key.private_key_to_pem().unwrap()).is_err());
+    }
+}
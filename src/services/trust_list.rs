@@ -0,0 +1,2 @@
+pub mod data_interface;
+pub mod trust_list_service;
@@ -0,0 +1,96 @@
+use chrono::Utc;
+use log::info;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, Set,
+    Statement,
+};
+use uuid::Uuid;
+
+use crate::entities::{
+    entities::{PublicKeyChangeEntity, TrustListVersionEntity},
+    models::{PublicKeyChangeActiveModel, PublicKeyChangeModel, TrustListVersionActiveModel},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyChangeType {
+    Added,
+    Updated,
+}
+
+impl KeyChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyChangeType::Added => "added",
+            KeyChangeType::Updated => "updated",
+        }
+    }
+}
+
+pub struct TrustListVersionService {}
+
+impl TrustListVersionService {
+    /// Current trust list version, zero until a sweeper changes a public key
+    pub async fn get_current_version(db: &DatabaseConnection) -> Result<i64, sea_orm::DbErr> {
+        Ok(TrustListVersionEntity::find_latest()
+            .one(db)
+            .await?
+            .map_or(0, |latest| latest.version))
+    }
+
+    /// Time (unix seconds) the current trust list version was created, `None` before the first
+    pub async fn get_last_modified(db: &DatabaseConnection) -> Result<Option<i64>, sea_orm::DbErr> {
+        Ok(TrustListVersionEntity::find_latest()
+            .one(db)
+            .await?
+            .map(|latest| latest.created_at))
+    }
+
+    /// Creates the next trust list version and records which public key changed with it. Runs in
+    /// the transaction that writes the key so that a key is never stored without its change; the
+    /// version table is locked until the transaction ends, concurrent sweepers wait for their turn
+    pub async fn bump_version(
+        txn: &DatabaseTransaction,
+        public_key_id: &Uuid,
+        change_type: KeyChangeType,
+    ) -> anyhow::Result<i64> {
+        txn.execute(Statement::from_string(
+            DbBackend::Postgres,
+            "LOCK TABLE trust_list_version IN SHARE ROW EXCLUSIVE MODE".to_owned(),
+        ))
+        .await?;
+        let version = match TrustListVersionEntity::find_latest().one(txn).await? {
+            Some(latest) => latest.version + 1,
+            None => 1,
+        };
+        let now = Utc::now().timestamp();
+        let db_registry = TrustListVersionActiveModel {
+            id: Set(Uuid::new_v4()),
+            version: Set(version),
+            created_at: Set(now),
+        };
+        db_registry.insert(txn).await?;
+        let db_registry = PublicKeyChangeActiveModel {
+            id: Set(Uuid::new_v4()),
+            version: Set(version),
+            public_key_id: Set(*public_key_id),
+            change_type: Set(change_type.as_str().to_owned()),
+            created_at: Set(now),
+        };
+        db_registry.insert(txn).await?;
+        info!(
+            "New trust list version {} ({} public key {})",
+            version,
+            change_type.as_str(),
+            public_key_id
+        );
+        Ok(version)
+    }
+
+    /// Changes recorded after `version`, oldest first
+    pub async fn find_changes_since(
+        db: &DatabaseConnection,
+        version: i64,
+    ) -> Result<Vec<PublicKeyChangeModel>, sea_orm::DbErr> {
+        PublicKeyChangeEntity::find_since(version).all(db).await
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use clap::__macro_refs::once_cell::sync::OnceCell;
+use log::{debug, error, info};
+use rocket::serde::json::Json;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        cache_conditions::CacheConditions,
+        response::{
+            public_key_response_dto::JwksKey,
+            signing_key_response_dto::SigningKeyResponseDto,
+            trust_list_response_dto::{
+                SignedTrustListResponseDto, TrustListChangesResponseDto, TrustListClaims,
+            },
+        },
+    },
+    entities::models::PublicKeyChangeModel,
+    responses::{
+        error_message::ErrorMessage, generic_response::Responses, success_messages::SuccessMessage,
+    },
+    services::{
+        public_key::data_interface::PublicKeyService,
+        signing::jws_signer::JwsSigner,
+        trust_list::data_interface::{KeyChangeType, TrustListVersionService},
+    },
+    utils::utils::Utils,
+};
+
+const TRUST_LIST_TYP: &str = "trust-list+jwt";
+
+/// validity of a signed trust list when `TRUST_LIST_VALIDITY_SECONDS` is not set
+const DEFAULT_TRUST_LIST_VALIDITY_SECONDS: i64 = 86400;
+
+/// signer loaded once from `TRUST_LIST_PRIVATE_KEY_FILE`; `None` when the export is not configured
+static TRUST_LIST_SIGNER: OnceCell<Option<JwsSigner>> = OnceCell::new();
+
+pub fn get_trust_list_signer() -> Option<&'static JwsSigner> {
+    TRUST_LIST_SIGNER
+        .get_or_init(|| JwsSigner::load("TRUST_LIST_PRIVATE_KEY_FILE", "trust list"))
+        .as_ref()
+}
+
+fn get_trust_list_validity() -> i64 {
+    match Utils::get_env_or_err("TRUST_LIST_VALIDITY_SECONDS") {
+        Ok(v) => match v.parse::<i64>() {
+            Ok(v) if v > 0 => v,
+            _ => {
+                info!(
+                    "Invalid TRUST_LIST_VALIDITY_SECONDS '{}', using {}",
+                    v, DEFAULT_TRUST_LIST_VALIDITY_SECONDS
+                );
+                DEFAULT_TRUST_LIST_VALIDITY_SECONDS
+            }
+        },
+        Err(_) => DEFAULT_TRUST_LIST_VALIDITY_SECONDS,
+    }
+}
+
+pub fn get_trust_list_claims(
+    version: i64,
+    keys: Vec<JwksKey>,
+    iat: i64,
+    validity: i64,
+) -> TrustListClaims {
+    TrustListClaims {
+        version,
+        iat,
+        exp: iat + validity,
+        keys,
+    }
+}
+
+fn bad_request<T>(
+    message: &'static str,
+    trace_id: String,
+) -> Responses<T, Json<ErrorMessage<'static>>> {
+    Responses::BadRequest(Json::from(ErrorMessage { message, trace_id }))
+}
+
+/// Signs the trusted keys of the public directory along with their version and freshness
+pub async fn get_signed_trust_list(
+    db: &DatabaseConnection,
+    public_directory_contract_address: &str,
+    chain_id: &str,
+) -> Responses<Json<SuccessMessage<SignedTrustListResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id = Uuid::new_v4().to_string();
+    let signer = match get_trust_list_signer() {
+        Some(v) => v,
+        None => return bad_request("Trust list export is not configured", trace_id),
+    };
+    let keys =
+        match PublicKeyService::find_jwks_keys(db, public_directory_contract_address, chain_id)
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
+                return bad_request("Internal error when retrieving public keys", trace_id);
+            }
+        };
+    let version = match TrustListVersionService::get_current_version(db).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
+            return bad_request("Unable to get the trust list version", trace_id);
+        }
+    };
+    let now = Utc::now().timestamp();
+    let claims = get_trust_list_claims(version, keys, now, get_trust_list_validity());
+    match signer.sign(TRUST_LIST_TYP, &claims) {
+        Ok(trust_list) => {
+            debug!(
+                "TRACE_ID: {}, trust list version {} signed",
+                trace_id, claims.version
+            );
+            Responses::Sucess(Json::from(SuccessMessage {
+                data: SignedTrustListResponseDto {
+                    version: claims.version,
+                    trust_list,
+                },
+                trace_id,
+            }))
+        }
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, e);
+            bad_request("Unable to sign the trust list", trace_id)
+        }
+    }
+}
+
+/// Ids of the keys changed by `changes`, in change order, and those among them that were added
+pub fn get_changed_public_key_ids(changes: &[PublicKeyChangeModel]) -> (Vec<Uuid>, HashSet<Uuid>) {
+    let mut ids = Vec::new();
+    let mut added = HashSet::new();
+    for change in changes {
+        if !ids.contains(&change.public_key_id) {
+            ids.push(change.public_key_id);
+        }
+        if change.change_type == KeyChangeType::Added.as_str() {
+            added.insert(change.public_key_id);
+        }
+    }
+    (ids, added)
+}
+
+async fn get_changes(
+    db: &DatabaseConnection,
+    public_directory_contract_address: &str,
+    chain_id: &str,
+    since: i64,
+    version: i64,
+) -> Result<TrustListChangesResponseDto, sea_orm::DbErr> {
+    let mut changes = TrustListChangesResponseDto {
+        since,
+        version,
+        added: vec![],
+        updated: vec![],
+        removed: vec![],
+    };
+    // a full sync only needs the keys trusted now, not the removed ones
+    if since == 0 {
+        changes.added =
+            PublicKeyService::find_jwks_keys(db, public_directory_contract_address, chain_id)
+                .await?;
+        return Ok(changes);
+    }
+    let (ids, added) =
+        get_changed_public_key_ids(&TrustListVersionService::find_changes_since(db, since).await?);
+    if ids.is_empty() {
+        return Ok(changes);
+    }
+    let trusted_ids = PublicKeyService::find_trusted_public_keys_by_ids(
+        db,
+        public_directory_contract_address,
+        chain_id,
+        &ids,
+    )
+    .await?
+    .into_iter()
+    .map(|registry| registry.id)
+    .collect::<HashSet<_>>();
+    // keys of other public directories are not part of this trust list
+    let registries = PublicKeyService::find_public_keys_by_ids(
+        db,
+        public_directory_contract_address,
+        chain_id,
+        &ids,
+    )
+    .await?;
+    let (trusted, untrusted): (Vec<_>, Vec<_>) = registries
+        .into_iter()
+        .partition(|registry| trusted_ids.contains(&registry.id));
+    let (new, updated): (Vec<_>, Vec<_>) = trusted
+        .into_iter()
+        .partition(|registry| added.contains(&registry.id));
+    changes.added = PublicKeyService::to_jwks_keys(new);
+    changes.updated = PublicKeyService::to_jwks_keys(updated);
+    changes.removed = PublicKeyService::to_jwks_keys(untrusted);
+    Ok(changes)
+}
+
+/// Keys added, updated and removed from the public directory trust list since the `since`
+/// version; `since` zero returns every trusted key as added
+pub async fn get_trust_list_changes_since(
+    db: &DatabaseConnection,
+    public_directory_contract_address: &str,
+    chain_id: &str,
+    since: i64,
+    conditions: &CacheConditions,
+) -> Responses<Json<SuccessMessage<TrustListChangesResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id = Uuid::new_v4().to_string();
+    let version = match TrustListVersionService::get_current_version(db).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
+            return bad_request("Unable to get the trust list version", trace_id);
+        }
+    };
+    if since < 0 || since > version {
+        let message = "'since' must be between zero and the current trust list version";
+        error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, message);
+        return bad_request(message, trace_id);
+    }
+    match get_changes(
+        db,
+        public_directory_contract_address,
+        chain_id,
+        since,
+        version,
+    )
+    .await
+    {
+        Ok(data) => {
+            let validators = PublicKeyService::get_cache_validators(db, &data, true).await;
+            Responses::cached(
+                Json::from(SuccessMessage { data, trace_id }),
+                validators,
+                conditions,
+            )
+        }
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {}", trace_id, &e);
+            bad_request("Internal error when retrieving public keys", trace_id)
+        }
+    }
+}
+
+/// Public key offline verifiers check the trust list signature with
+pub fn get_trust_list_public_key(
+) -> Responses<Json<SuccessMessage<SigningKeyResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id = Uuid::new_v4().to_string();
+    let signer = match get_trust_list_signer() {
+        Some(v) => v,
+        None => return bad_request("Trust list export is not configured", trace_id),
+    };
+    match signer.get_public_key() {
+        Ok(data) => Responses::Sucess(Json::from(SuccessMessage { data, trace_id })),
+        Err(e) => {
+            error!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, e);
+            bad_request("Unable to get the trust list public key", trace_id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lacpass_verifier::types::jwk::Jwk;
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        nid::Nid,
+    };
+
+    use crate::services::signing::jws::{decode_jws, verify_es256_signature};
+
+    use super::*;
+
+    fn jwks_key(kid: &str, country: &str) -> JwksKey {
+        JwksKey {
+            jwk: Jwk {
+                alg: None,
+                r#use: None,
+                kty: Some("EC".to_owned()),
+                kid: Some(kid.to_owned()),
+                x5c: Some(vec!["MIIB".to_owned()]),
+                x5t: None,
+                n: None,
+                e: None,
+                x: Some("eA".to_owned()),
+                y: Some("eQ".to_owned()),
+                crv: Some("P-256".to_owned()),
+            },
+            x5t_s256: "dGh1bWI".to_owned(),
+            country: country.to_owned(),
+        }
+    }
+
+    fn change(
+        version: i64,
+        public_key_id: Uuid,
+        change_type: KeyChangeType,
+    ) -> PublicKeyChangeModel {
+        PublicKeyChangeModel {
+            id: Uuid::new_v4(),
+            version,
+            public_key_id,
+            change_type: change_type.as_str().to_owned(),
+            created_at: 1700000000,
+        }
+    }
+
+    #[test]
+    fn get_changed_public_key_ids_test() {
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let changes = vec![
+            change(3, second, KeyChangeType::Updated),
+            change(4, first, KeyChangeType::Added),
+            change(5, second, KeyChangeType::Updated),
+            change(6, third, KeyChangeType::Added),
+            change(7, first, KeyChangeType::Updated),
+        ];
+        let (ids, added) = get_changed_public_key_ids(&changes);
+        assert_eq!(ids, vec![second, first, third]);
+        assert_eq!(added, HashSet::from([first, third]));
+
+        let (ids, added) = get_changed_public_key_ids(&[]);
+        assert!(ids.is_empty());
+        assert!(added.is_empty());
+    }
+
+    #[test]
+    fn sign_trust_list_test() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let signer = JwsSigner::new(&key.private_key_to_pem().unwrap()).unwrap();
+        let claims = get_trust_list_claims(7, vec![jwks_key("a2lk", "CHL")], 1700000100, 3600);
+        let trust_list = signer.sign(TRUST_LIST_TYP, &claims).unwrap();
+
+        let jws = decode_jws(&trust_list).unwrap();
+        let jwk = signer.get_public_key().unwrap().jwk;
+        assert!(
+            verify_es256_signature(&jwk, jws.signing_input.as_bytes(), &jws.signature).unwrap()
+        );
+        let decoded: TrustListClaims = serde_json::from_slice(&jws.payload).unwrap();
+        assert_eq!(decoded.version, 7);
+        assert_eq!(decoded.iat, 1700000100);
+        assert_eq!(decoded.exp, 1700003700);
+        assert_eq!(decoded.keys.len(), 1);
+        assert_eq!(decoded.keys[0].jwk.kid.as_deref(), Some("a2lk"));
+        assert_eq!(decoded.keys[0].country, "CHL");
+    }
+}
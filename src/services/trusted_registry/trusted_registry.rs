@@ -10,7 +10,10 @@ use crate::{
         did::did_registry_worker_service::DidRegistryWorkerService,
         public_directory::index::PublicDirectoryService,
         public_directory::public_directory_worker_service::PublicDirectoryWorkerService,
-        public_key::source_1_worker_service::ExternalSource1WorkerService,
+        public_key::{
+            chain_worker_service::ChainWorkerService,
+            source_1_worker_service::ExternalSource1WorkerService,
+        },
     },
     utils::utils::Utils,
 };
@@ -104,6 +107,13 @@ impl TrustedRegistry {
                         info!("Skipping sweep to external souce 1 since it was not set on startup");
                     }
                 }
+                // keys inserted above were just evaluated, this catches the older ones
+                match ChainWorkerService::new().sweep(&db).await {
+                    Ok(_) => {
+                        info!("Sucessfully re-evaluated the CSCA chain of the public keys")
+                    }
+                    Err(e) => return Err(e.into()),
+                }
                 Ok(())
             }
             Err(e) => {
@@ -1,2 +1,5 @@
-pub mod certificate_error;
 pub mod certificate_service;
+pub mod fhir_service;
+pub mod qr_decoder;
+pub mod shc_service;
+pub mod vds_nc_service;
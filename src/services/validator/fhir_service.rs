@@ -0,0 +1,432 @@
+use chrono::{SecondsFormat, Utc};
+use log::debug;
+use rocket::serde::json::Json;
+use uuid::Uuid;
+
+use crate::{
+    dto::response::{
+        fhir_response_dto::{
+            FhirBundle, FhirBundleEntry, FhirCodeableConcept, FhirCoding, FhirComposition,
+            FhirCompositionSection, FhirExtension, FhirHumanName, FhirIdentifier, FhirImmunization,
+            FhirImmunizationPerformer, FhirImmunizationProtocolApplied, FhirMeta, FhirPatient,
+            FhirReference, FhirResource,
+        },
+        hc1_response_dto::{CodeSystem, DdccCoreDataSet, HC1ValidationResponseDto},
+    },
+    responses::{
+        error_message::ErrorMessage, generic_response::Responses,
+        negotiated_response::NegotiatedResponses, success_messages::SuccessMessage,
+    },
+};
+
+/// WHO DDCC implementation guide canonical url
+const DDCC_IG: &str = "http://worldhealthorganization.github.io/ddcc";
+const LOINC_SYSTEM: &str = "http://loinc.org";
+/// LOINC "Immunization summary report"
+const LOINC_IMMUNIZATION_SUMMARY: &str = "82593-5";
+/// LOINC "History of Immunization Narrative"
+const LOINC_IMMUNIZATION_HISTORY: &str = "11369-6";
+/// fhir administrative genders, DDCC `sex` values outside of them are left out
+const ADMINISTRATIVE_GENDERS: [&str; 4] = ["male", "female", "other", "unknown"];
+
+fn structure_definition(name: &str) -> String {
+    format!("{}/StructureDefinition/{}", DDCC_IG, name)
+}
+
+fn profile(name: &str) -> FhirMeta {
+    FhirMeta {
+        profile: vec![structure_definition(name)],
+    }
+}
+
+fn coding(code_system: &CodeSystem) -> FhirCoding {
+    FhirCoding {
+        system: code_system.system.clone(),
+        code: code_system.code.clone(),
+        display: None,
+    }
+}
+
+fn codeable_concept(code_system: &CodeSystem) -> FhirCodeableConcept {
+    FhirCodeableConcept {
+        coding: vec![coding(code_system)],
+    }
+}
+
+fn loinc(code: &str) -> FhirCodeableConcept {
+    FhirCodeableConcept {
+        coding: vec![FhirCoding {
+            system: Some(LOINC_SYSTEM.to_owned()),
+            code: Some(code.to_owned()),
+            display: None,
+        }],
+    }
+}
+
+fn reference(full_url: &str) -> FhirReference {
+    FhirReference {
+        reference: Some(full_url.to_owned()),
+        ..Default::default()
+    }
+}
+
+fn full_url() -> String {
+    format!("urn:uuid:{}", Uuid::new_v4())
+}
+
+/// Builds the DDCC:VS document (Composition, Patient and Immunization) out of a verified DDCC;
+/// fails if the DDCC has no vaccination
+pub fn get_ddcc_vs_bundle(
+    ddcc_core_data_set: &DdccCoreDataSet,
+    timestamp: &str,
+) -> anyhow::Result<FhirBundle> {
+    let vaccination = match &ddcc_core_data_set.vaccination {
+        Some(v) => v,
+        None => return Err(anyhow::anyhow!("The DDCC has no vaccination")),
+    };
+    let (composition_url, patient_url, immunization_url) = (full_url(), full_url(), full_url());
+    let certificate = ddcc_core_data_set.certificate.as_ref();
+    let hcid = certificate
+        .and_then(|c| c.hcid.value.clone())
+        .map(|value| FhirIdentifier {
+            system: None,
+            value: Some(value),
+        });
+
+    let patient = FhirPatient {
+        meta: profile("DDCCPatient"),
+        identifier: ddcc_core_data_set
+            .identifier
+            .iter()
+            .map(|value| FhirIdentifier {
+                system: None,
+                value: Some(value.to_owned()),
+            })
+            .collect(),
+        name: vec![FhirHumanName {
+            text: ddcc_core_data_set.name.clone(),
+        }],
+        birth_date: ddcc_core_data_set.birth_date.clone(),
+        gender: ddcc_core_data_set
+            .sex
+            .as_ref()
+            .map(|sex| sex.to_lowercase())
+            .filter(|sex| ADMINISTRATIVE_GENDERS.contains(&sex.as_str())),
+    };
+
+    let mut extensions = vec![
+        FhirExtension {
+            url: structure_definition("DDCCVaccineBrand"),
+            value_coding: Some(coding(&vaccination.brand)),
+            ..Default::default()
+        },
+        FhirExtension {
+            url: structure_definition("DDCCCountryOfEvent"),
+            value_code: vaccination.country.code.clone(),
+            ..Default::default()
+        },
+    ];
+    if let Some(maholder) = &vaccination.maholder {
+        extensions.push(FhirExtension {
+            url: structure_definition("DDCCVaccineMarketAuthorization"),
+            value_coding: Some(coding(maholder)),
+            ..Default::default()
+        });
+    }
+    if let Some(valid_from) = &vaccination.valid_from {
+        extensions.push(FhirExtension {
+            url: structure_definition("DDCCVaccineValidFrom"),
+            value_date_time: Some(valid_from.to_owned()),
+            ..Default::default()
+        });
+    }
+    let immunization = FhirImmunization {
+        meta: profile("DDCCImmunization"),
+        extension: extensions,
+        status: "completed".to_owned(),
+        vaccine_code: codeable_concept(&vaccination.vaccine),
+        patient: reference(&patient_url),
+        occurrence_date_time: vaccination.date.clone(),
+        location: vaccination.centre.as_ref().map(|centre| FhirReference {
+            display: Some(centre.to_owned()),
+            ..Default::default()
+        }),
+        manufacturer: vaccination
+            .manufacturer
+            .as_ref()
+            .map(|manufacturer| FhirReference {
+                identifier: Some(FhirIdentifier {
+                    system: manufacturer.system.clone(),
+                    value: manufacturer.code.clone(),
+                }),
+                ..Default::default()
+            }),
+        lot_number: vaccination.lot.clone(),
+        performer: vaccination
+            .practitioner
+            .iter()
+            .map(|practitioner| FhirImmunizationPerformer {
+                actor: FhirReference {
+                    identifier: Some(FhirIdentifier {
+                        system: None,
+                        value: practitioner.value.clone(),
+                    }),
+                    ..Default::default()
+                },
+            })
+            .collect(),
+        protocol_applied: vec![FhirImmunizationProtocolApplied {
+            target_disease: vaccination.disease.iter().map(codeable_concept).collect(),
+            dose_number_positive_int: vaccination.dose,
+            series_doses_positive_int: vaccination.total_doses,
+        }],
+    };
+
+    let composition = FhirComposition {
+        meta: profile("DDCCVSComposition"),
+        identifier: hcid.clone(),
+        status: "final".to_owned(),
+        composition_type: loinc(LOINC_IMMUNIZATION_SUMMARY),
+        subject: reference(&patient_url),
+        date: timestamp.to_owned(),
+        author: certificate
+            .and_then(|c| c.issuer.identifier.value.clone())
+            .map(|value| FhirReference {
+                identifier: Some(FhirIdentifier {
+                    system: None,
+                    value: Some(value),
+                }),
+                ..Default::default()
+            })
+            .into_iter()
+            .collect(),
+        title: "International Certificate of Vaccination or Prophylaxis".to_owned(),
+        section: vec![FhirCompositionSection {
+            code: loinc(LOINC_IMMUNIZATION_HISTORY),
+            focus: reference(&immunization_url),
+            entry: vec![reference(&immunization_url)],
+        }],
+    };
+
+    Ok(FhirBundle {
+        resource_type: "Bundle".to_owned(),
+        meta: profile("DDCCDocument"),
+        identifier: hcid,
+        bundle_type: "document".to_owned(),
+        timestamp: timestamp.to_owned(),
+        entry: vec![
+            FhirBundleEntry {
+                full_url: composition_url,
+                resource: FhirResource::Composition(composition),
+            },
+            FhirBundleEntry {
+                full_url: patient_url,
+                resource: FhirResource::Patient(patient),
+            },
+            FhirBundleEntry {
+                full_url: immunization_url,
+                resource: FhirResource::Immunization(immunization),
+            },
+        ],
+    })
+}
+
+/// Returns the DDCC:VS bundle instead of the verification result when FHIR was requested and the
+/// certificate is valid; invalid certificates keep the json result so that the failed checks
+/// are visible. Valid certificates without a DDCC vaccination (EU DCC, DDCC test or recovery)
+/// are answered with `406 Not Acceptable`
+pub fn negotiate_response(
+    response: Responses<
+        Json<SuccessMessage<HC1ValidationResponseDto>>,
+        Json<ErrorMessage<'static>>,
+    >,
+    fhir_requested: bool,
+) -> NegotiatedResponses<
+    Json<SuccessMessage<HC1ValidationResponseDto>>,
+    Json<FhirBundle>,
+    Json<ErrorMessage<'static>>,
+> {
+    if fhir_requested {
+        to_fhir_response(response)
+    } else {
+        NegotiatedResponses::Json(response)
+    }
+}
+
+fn to_fhir_response(
+    response: Responses<
+        Json<SuccessMessage<HC1ValidationResponseDto>>,
+        Json<ErrorMessage<'static>>,
+    >,
+) -> NegotiatedResponses<
+    Json<SuccessMessage<HC1ValidationResponseDto>>,
+    Json<FhirBundle>,
+    Json<ErrorMessage<'static>>,
+> {
+    let result = match response {
+        Responses::Sucess(result) if result.data.is_valid => result.into_inner(),
+        response => return NegotiatedResponses::Json(response),
+    };
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let bundle = match &result.data.ddcc_core_data_set {
+        Some(ddcc_core_data_set) => get_ddcc_vs_bundle(ddcc_core_data_set, &timestamp),
+        None => Err(anyhow::anyhow!("The certificate is not a DDCC")),
+    };
+    match bundle {
+        Ok(bundle) => NegotiatedResponses::Fhir(Json::from(bundle)),
+        Err(e) => {
+            debug!("TRACE_ID: {}, DESCRIPTION: {}", result.trace_id, e);
+            NegotiatedResponses::NotAcceptable(Json::from(ErrorMessage {
+                message: "FHIR output is only available for DDCC vaccination certificates",
+                trace_id: result.trace_id,
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::response::hc1_response_dto::{Certificate, Identifier, Vaccination, Value};
+
+    use super::*;
+
+    fn code_system(code: &str) -> CodeSystem {
+        CodeSystem {
+            code: Some(code.to_owned()),
+            system: Some("http://id.who.int/icd11/mms".to_owned()),
+        }
+    }
+
+    fn ddcc_core_data_set() -> DdccCoreDataSet {
+        DdccCoreDataSet {
+            vaccination: Some(Vaccination {
+                date: "2023-09-01".to_owned(),
+                dose: 1,
+                vaccine: code_system("XM68M6"),
+                country: CodeSystem {
+                    code: Some("CHL".to_owned()),
+                    system: None,
+                },
+                maholder: None,
+                lot: "VC35679".to_owned(),
+                centre: Some("Vaccination Site".to_owned()),
+                brand: code_system("XM8NQ0"),
+                manufacturer: None,
+                valid_from: None,
+                total_doses: Some(2),
+                practitioner: None,
+                disease: Some(code_system("RA01")),
+                next_dose: None,
+                extensions: Default::default(),
+            }),
+            test_result: None,
+            recovery: None,
+            resource_type: None,
+            birth_date: Some("1990-01-01".to_owned()),
+            name: "Jane Doe".to_owned(),
+            identifier: Some("123456".to_owned()),
+            sex: Some("female".to_owned()),
+            certificate: Some(Certificate {
+                hcid: Value {
+                    value: Some("HCID-1".to_owned()),
+                },
+                period: None,
+                issuer: Identifier {
+                    identifier: Value {
+                        value: Some("MoH".to_owned()),
+                    },
+                },
+                version: "RC-2.0.0".to_owned(),
+                extensions: Default::default(),
+            }),
+            extensions: Default::default(),
+        }
+    }
+
+    #[test]
+    fn get_ddcc_vs_bundle_test() {
+        let bundle = get_ddcc_vs_bundle(&ddcc_core_data_set(), "2023-10-01T10:00:00Z").unwrap();
+        let bundle = serde_json::to_value(bundle).unwrap();
+        assert_eq!(bundle["resourceType"], "Bundle");
+        assert_eq!(bundle["type"], "document");
+        assert_eq!(bundle["identifier"]["value"], "HCID-1");
+
+        let entries = bundle["entry"].as_array().unwrap();
+        let resource_types = entries
+            .iter()
+            .map(|entry| entry["resource"]["resourceType"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resource_types,
+            vec!["Composition", "Patient", "Immunization"]
+        );
+
+        let (composition, patient, immunization) = (
+            &entries[0]["resource"],
+            &entries[1]["resource"],
+            &entries[2]["resource"],
+        );
+        assert_eq!(composition["subject"]["reference"], entries[1]["fullUrl"]);
+        assert_eq!(
+            composition["section"][0]["entry"][0]["reference"],
+            entries[2]["fullUrl"]
+        );
+        assert_eq!(composition["author"][0]["identifier"]["value"], "MoH");
+        assert_eq!(patient["name"][0]["text"], "Jane Doe");
+        assert_eq!(patient["gender"], "female");
+        assert_eq!(immunization["patient"]["reference"], entries[1]["fullUrl"]);
+        assert_eq!(immunization["vaccineCode"]["coding"][0]["code"], "XM68M6");
+        assert_eq!(immunization["lotNumber"], "VC35679");
+        assert_eq!(
+            immunization["protocolApplied"][0]["doseNumberPositiveInt"],
+            1
+        );
+        assert_eq!(
+            immunization["protocolApplied"][0]["seriesDosesPositiveInt"],
+            2
+        );
+    }
+
+    fn validation_result(
+        ddcc_core_data_set: DdccCoreDataSet,
+    ) -> Responses<Json<SuccessMessage<HC1ValidationResponseDto>>, Json<ErrorMessage<'static>>>
+    {
+        Responses::Sucess(Json::from(SuccessMessage {
+            data: HC1ValidationResponseDto {
+                is_valid: true,
+                kid: None,
+                checks: vec![],
+                payload_type: None,
+                ddcc_core_data_set: Some(ddcc_core_data_set),
+                eu_dcc: None,
+                policy: None,
+                receipt: None,
+            },
+            trace_id: Uuid::new_v4().to_string(),
+        }))
+    }
+
+    #[test]
+    fn negotiate_response_test() {
+        let response = negotiate_response(validation_result(ddcc_core_data_set()), true);
+        assert!(matches!(response, NegotiatedResponses::Fhir(_)));
+
+        let response = negotiate_response(validation_result(ddcc_core_data_set()), false);
+        assert!(matches!(
+            response,
+            NegotiatedResponses::Json(Responses::Sucess(_))
+        ));
+
+        let mut test_result = ddcc_core_data_set();
+        test_result.vaccination = None;
+        let response = negotiate_response(validation_result(test_result), true);
+        assert!(matches!(response, NegotiatedResponses::NotAcceptable(_)));
+    }
+
+    #[test]
+    fn get_ddcc_vs_bundle_without_vaccination_test() {
+        let mut ddcc_core_data_set = ddcc_core_data_set();
+        ddcc_core_data_set.vaccination = None;
+        assert!(get_ddcc_vs_bundle(&ddcc_core_data_set, "2023-10-01T10:00:00Z").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::{fmt, io::Cursor};
+
+use image::{
+    io::{Limits, Reader},
+    DynamicImage, GrayImage, ImageError,
+};
+use log::debug;
+
+/// Largest width and height accepted; a QR code does not need more to be read
+const MAX_IMAGE_DIMENSION: u32 = 4096;
+/// Largest amount of memory the image decoder may allocate
+const MAX_IMAGE_ALLOC: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QrDecodingError {
+    InvalidImage,
+    ImageTooLarge,
+    NoQrCode,
+    MultipleQrCodes(usize),
+    InvalidQrCode,
+}
+
+impl QrDecodingError {
+    /// Message returned to the client
+    pub fn message(&self) -> &'static str {
+        match self {
+            QrDecodingError::InvalidImage => "Unable to read image, expected png or jpeg",
+            QrDecodingError::ImageTooLarge => "Image is too large",
+            QrDecodingError::NoQrCode => "No QR code found in image",
+            QrDecodingError::MultipleQrCodes(_) => "More than one QR code found in image",
+            QrDecodingError::InvalidQrCode => "Unable to decode the QR code found in image",
+        }
+    }
+}
+
+impl fmt::Display for QrDecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QrDecodingError::MultipleQrCodes(count) => {
+                write!(f, "{} ({} found)", self.message(), count)
+            }
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+/// Decodes a png or jpeg image within the dimension and allocation limits
+fn load_image(bytes: &[u8]) -> Result<DynamicImage, QrDecodingError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_IMAGE_ALLOC);
+    let mut reader = match Reader::new(Cursor::new(bytes)).with_guessed_format() {
+        Ok(v) => v,
+        Err(e) => {
+            debug!("Unable to guess image format, error was: {:?}", e);
+            return Err(QrDecodingError::InvalidImage);
+        }
+    };
+    reader.limits(limits);
+    match reader.decode() {
+        Ok(img) => Ok(img),
+        Err(ImageError::Limits(e)) => {
+            debug!("Image exceeds the decoding limits, error was: {:?}", e);
+            Err(QrDecodingError::ImageTooLarge)
+        }
+        Err(e) => {
+            debug!("Unable to load image, error was: {:?}", e);
+            Err(QrDecodingError::InvalidImage)
+        }
+    }
+}
+
+/// Returns the text of the only QR code found in a png or jpeg image; decoding runs on the
+/// blocking thread pool so that large images do not stall the async workers
+pub async fn decode_qr_image(bytes: Vec<u8>) -> Result<String, QrDecodingError> {
+    let decoding = tokio::task::spawn_blocking(move || {
+        load_image(&bytes).and_then(|img| decode_qr_gray_image(img.to_luma8()))
+    });
+    match decoding.await {
+        Ok(result) => result,
+        Err(e) => {
+            debug!("QR decoding task failed, error was: {:?}", e);
+            Err(QrDecodingError::InvalidImage)
+        }
+    }
+}
+
+pub fn decode_qr_gray_image(img: GrayImage) -> Result<String, QrDecodingError> {
+    let mut prepared = rqrr::PreparedImage::prepare(img);
+    let grids = prepared.detect_grids();
+    match grids.len() {
+        0 => Err(QrDecodingError::NoQrCode),
+        1 => match grids[0].decode() {
+            Ok((_, content)) => Ok(content),
+            Err(e) => {
+                debug!("Unable to decode QR code, error was: {:?}", e);
+                Err(QrDecodingError::InvalidQrCode)
+            }
+        },
+        count => Err(QrDecodingError::MultipleQrCodes(count)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageOutputFormat, Luma};
+    use qrcode::QrCode;
+
+    fn encode_png(img: GrayImage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::ImageLuma8(img)
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[rocket::async_test]
+    async fn decode_qr_image_test() {
+        // EdDSA vector of the verifier crate
+        let data = "HC1:NCFOXN%TSMAHO.J4VK+Q6:KAX/P60SPCHRX42NP2$36R5K1TWMAKHRIJAN:7X6BMF6.TA9ZII7J9UJENS3DJ4RL5OI-MJQMIFWBJS4:54QP4Y:7:X6LDCFLEFI9E2LBHHGKLO-K%FGRH91 CKF5I76615+*P2$P-8R3+LZ/MOT6OQ0M+Q *PT*OE+Q:COEV4XHM2:M2HPXEPXCR*88FDOT*OYGO.20GMKMT3EE3GJDXUHTT6+9K+:QI SEJK5U7W040NIH/EPTV*QE40C 5RF.JMU77$AO4GPP2UEQQTSZR5KPERPBK$VH0LDWNB-JLTHUKHC0TV50/73XAA";
+        let img = QrCode::new(data.as_bytes())
+            .unwrap()
+            .render::<Luma<u8>>()
+            .build();
+        assert_eq!(decode_qr_image(encode_png(img)).await, Ok(data.to_owned()));
+    }
+
+    #[rocket::async_test]
+    async fn decode_qr_image_invalid_image_test() {
+        assert_eq!(
+            decode_qr_image(b"HC1:NCFOXN%TS3DH3ZSUZK+.V0ETD%65NL-AH".to_vec()).await,
+            Err(QrDecodingError::InvalidImage)
+        );
+    }
+
+    #[rocket::async_test]
+    async fn decode_qr_image_too_large_test() {
+        let img = GrayImage::from_pixel(MAX_IMAGE_DIMENSION + 1, 1, Luma([255u8]));
+        assert_eq!(
+            decode_qr_image(encode_png(img)).await,
+            Err(QrDecodingError::ImageTooLarge)
+        );
+    }
+
+    #[test]
+    fn decode_qr_gray_image_without_qr_code_test() {
+        let img = GrayImage::from_pixel(200, 200, Luma([255u8]));
+        assert_eq!(decode_qr_gray_image(img), Err(QrDecodingError::NoQrCode));
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    dto::response::{
+        hc1_response_dto::{CheckCode, CheckStatus, CheckType, CodeSystem, VerificationCheck},
+        public_key_response_dto::Jwk,
+        shc_response_dto::{ShcImmunization, ShcValidationResponseDto},
+    },
+    responses::{
+        error_message::ErrorMessage, generic_response::Responses, success_messages::SuccessMessage,
+    },
+    services::{
+        disclosure::disclosure_profile::DisclosureProfile,
+        signing::jws::{decode_jws, verify_es256_signature},
+        validator::certificate_service::get_pem_keys_by_jwk_thumbprint,
+    },
+};
+use lacpass_verifier::{
+    cwt_claims::CwtClaims, hc1::get_claim_checks, key_provider::TrustedKey, x509::X509Utils,
+};
+use log::{debug, info};
+use rocket::serde::json::Json;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use uuid::Uuid;
+
+// reference: https://spec.smarthealth.cards
+const SHC_PREFIX: &str = "shc:/";
+/// numeric encoding: every character is written as two digits, its value minus this offset
+const SHC_NUMERIC_OFFSET: u8 = 45;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShcPayload {
+    pub iss: String,
+    pub nbf: Option<f64>,
+    pub vc: ShcVerifiableCredential,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShcVerifiableCredential {
+    pub credential_subject: ShcCredentialSubject,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShcCredentialSubject {
+    pub fhir_bundle: serde_json::Value,
+}
+
+/// Turns a numeric `shc:/` payload into the compact JWS it carries. Chunked cards
+/// (`shc:/1/2/...`) are not supported.
+pub fn decode_shc_numeric(data: &str) -> Result<String, VerificationCheck> {
+    let failed = |message: &str| {
+        VerificationCheck::failed(
+            CheckType::Decoding,
+            CheckCode::InvalidNumericEncoding,
+            message,
+        )
+    };
+    let data = data.trim();
+    let digits = match data.strip_prefix(SHC_PREFIX) {
+        Some(v) => v,
+        None => return Err(failed("Missing 'shc:/' prefix")),
+    };
+    if digits.contains('/') {
+        return Err(failed("Chunked SMART Health Cards are not supported"));
+    }
+    if digits.len() % 2 != 0 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(failed("Payload must be an even number of digits"));
+    }
+    digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let value = (pair[0] - b'0') * 10 + (pair[1] - b'0');
+            match value.checked_add(SHC_NUMERIC_OFFSET) {
+                Some(c) if c <= b'z' => Ok(c as char),
+                _ => Err(failed("Digit pair out of range")),
+            }
+        })
+        .collect::<Result<String, _>>()
+}
+
+fn get_string(value: &serde_json::Value, name: &str) -> Option<String> {
+    value
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_owned())
+}
+
+/// Returns the Immunization resources of a FHIR bundle; fails if there is no `entry` array
+pub fn get_immunizations(fhir_bundle: &serde_json::Value) -> anyhow::Result<Vec<ShcImmunization>> {
+    let entries = match fhir_bundle.get("entry").and_then(|v| v.as_array()) {
+        Some(v) => v,
+        None => {
+            let message = "No 'entry' array found in FHIR bundle";
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    };
+    Ok(entries
+        .iter()
+        .filter_map(|entry| entry.get("resource"))
+        .filter(|resource| get_string(resource, "resourceType").as_deref() == Some("Immunization"))
+        .map(|resource| {
+            let vaccine_code = resource
+                .get("vaccineCode")
+                .and_then(|v| v.get("coding"))
+                .and_then(|v| v.as_array())
+                .map(|coding| {
+                    coding
+                        .iter()
+                        .map(|c| CodeSystem {
+                            code: get_string(c, "code"),
+                            system: get_string(c, "system"),
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let performer = resource
+                .get("performer")
+                .and_then(|v| v.as_array())
+                .and_then(|performers| performers.get(0))
+                .and_then(|p| p.get("actor"))
+                .and_then(|actor| get_string(actor, "display"));
+            ShcImmunization {
+                status: get_string(resource, "status"),
+                vaccine_code,
+                occurrence_date_time: get_string(resource, "occurrenceDateTime"),
+                lot_number: get_string(resource, "lotNumber"),
+                performer,
+            }
+        })
+        .collect::<Vec<_>>())
+}
+
+/// Returns the keys usable at `issued_at` whose thumbprint matches `kid`, each one paired with its jwk
+async fn get_jwks_by_thumbprint(
+    db: &DatabaseConnection,
+    kid: &str,
+    issued_at: Option<i64>,
+) -> anyhow::Result<Vec<(TrustedKey, Jwk)>> {
+    Ok(get_pem_keys_by_jwk_thumbprint(db, kid)
+        .await?
+        .into_iter()
+        .filter(|trusted_key| trusted_key.is_usable_at(issued_at))
+        .filter_map(|trusted_key| {
+            let jwk = X509Utils::get_jwk_from_pem(trusted_key.pem.clone()).ok()?;
+            Some((trusted_key, jwk))
+        })
+        .collect::<Vec<_>>())
+}
+
+fn invalid_response(
+    checks: Vec<VerificationCheck>,
+    issuer: Option<String>,
+    trace_id: Uuid,
+) -> Responses<Json<SuccessMessage<ShcValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+    debug!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, checks);
+    Responses::Sucess(Json::from(SuccessMessage {
+        data: ShcValidationResponseDto {
+            is_valid: false,
+            kid: None,
+            country: None,
+            issuer,
+            checks,
+            immunizations: vec![],
+            receipt: None,
+        },
+        trace_id: trace_id.to_string(),
+    }))
+}
+
+pub async fn verify_shc(
+    db: &DatabaseConnection,
+    data: String,
+    disclosure: DisclosureProfile,
+) -> Responses<Json<SuccessMessage<ShcValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id: Uuid = Uuid::new_v4();
+    info!("New SMART Health Card verification request: {:?}", trace_id);
+    let mut checks = Vec::new();
+    let jws = match decode_shc_numeric(&data).and_then(|jws| decode_jws(&jws)) {
+        Ok(v) => v,
+        Err(check) => {
+            checks.push(check);
+            return invalid_response(checks, None, trace_id);
+        }
+    };
+    let payload: ShcPayload = match serde_json::from_slice(&jws.payload) {
+        Ok(v) => v,
+        Err(e) => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Decoding,
+                CheckCode::InvalidJws,
+                &format!("Invalid SMART Health Card payload: {}", e),
+            ));
+            return invalid_response(checks, None, trace_id);
+        }
+    };
+    checks.push(VerificationCheck::passed(CheckType::Decoding));
+    let issuer = Some(payload.iss.clone());
+
+    let immunizations = match get_immunizations(&payload.vc.credential_subject.fhir_bundle) {
+        Ok(v) => {
+            checks.push(VerificationCheck::passed(CheckType::Schema));
+            v
+        }
+        Err(e) => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Schema,
+                CheckCode::InvalidSchema,
+                &e.to_string(),
+            ));
+            vec![]
+        }
+    };
+
+    let kid = match jws.header.kid.clone() {
+        Some(v) => v,
+        None => {
+            checks.push(VerificationCheck::failed(
+                CheckType::KeyTrust,
+                CheckCode::KeyNotFound,
+                "No kid found in JWS header",
+            ));
+            return invalid_response(checks, issuer, trace_id);
+        }
+    };
+    let issued_at = payload.nbf.map(|nbf| nbf as i64);
+    let candidates = match get_jwks_by_thumbprint(db, &kid, issued_at).await {
+        Ok(v) => v,
+        Err(e) => {
+            let message = "Internal Error while getting keys";
+            debug!("TRACE_ID: {}, DESCRIPTION ({}): {:?}", trace_id, message, e);
+            return Responses::BadRequest(Json::from(ErrorMessage {
+                message,
+                trace_id: trace_id.to_string(),
+            }));
+        }
+    };
+    if candidates.is_empty() {
+        checks.push(
+            VerificationCheck::failed(
+                CheckType::KeyTrust,
+                CheckCode::KeyNotFound,
+                "No trusted key matches the JWS kid",
+            )
+            .with_kid(Some(kid.clone())),
+        );
+        return invalid_response(checks, issuer, trace_id);
+    }
+    let trusted_key = candidates.into_iter().find_map(|(trusted_key, jwk)| {
+        match verify_es256_signature(&jwk, jws.signing_input.as_bytes(), &jws.signature) {
+            Ok(true) => Some(trusted_key),
+            Ok(false) => None,
+            Err(e) => {
+                debug!(
+                    "TRACE_ID: {}, DESCRIPTION (signature verification, kid {}): {:?}",
+                    trace_id, kid, e
+                );
+                None
+            }
+        }
+    });
+    let country = trusted_key.as_ref().map(|k| k.country_code.clone());
+    match &trusted_key {
+        Some(_) => {
+            checks.push(
+                VerificationCheck::passed(CheckType::KeyTrust)
+                    .with_kid(Some(kid.clone()))
+                    .with_country(country.clone()),
+            );
+            checks.push(VerificationCheck::passed(CheckType::Signature).with_kid(Some(kid.clone())))
+        }
+        None => {
+            // a key matching the kid is not trusted unless it verifies the signature
+            checks.push(
+                VerificationCheck::skipped(
+                    CheckType::KeyTrust,
+                    "No trusted key matching the JWS kid verified the signature",
+                )
+                .with_kid(Some(kid.clone())),
+            );
+            checks.push(
+                VerificationCheck::failed(
+                    CheckType::Signature,
+                    CheckCode::InvalidSignature,
+                    "No trusted key verified the signature",
+                )
+                .with_kid(Some(kid.clone())),
+            )
+        }
+    }
+    // cards carry no expiration nor country; only `nbf` (issuance) against the key expiration applies
+    let claims = CwtClaims {
+        iss: None,
+        exp: None,
+        iat: issued_at,
+    };
+    let now = chrono::Utc::now().timestamp();
+    checks.extend(
+        get_claim_checks(&claims, trusted_key.as_ref(), now)
+            .into_iter()
+            .filter(|check| check.check == CheckType::KeyExpiry),
+    );
+
+    let is_valid = checks
+        .iter()
+        .all(|check| check.status != CheckStatus::Failed);
+    if !is_valid {
+        debug!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, checks);
+    }
+    Responses::Sucess(Json::from(SuccessMessage {
+        data: disclosure.apply_shc(ShcValidationResponseDto {
+            is_valid,
+            kid: trusted_key.map(|_| kid),
+            country,
+            issuer,
+            checks,
+            immunizations,
+            receipt: None,
+        }),
+        trace_id: trace_id.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_shc_numeric_test() {
+        // "eyJ" -> 'e' (101), 'y' (121), 'J' (74)
+        assert_eq!(decode_shc_numeric("shc:/567629").unwrap(), "eyJ");
+        assert!(decode_shc_numeric("shc:/5676295").is_err());
+        assert!(decode_shc_numeric("shc:/1/2/567629").is_err());
+        assert!(decode_shc_numeric("567629").is_err());
+    }
+
+    #[test]
+    fn get_immunizations_test() {
+        let bundle = serde_json::json!({
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                {
+                    "fullUrl": "resource:0",
+                    "resource": {
+                        "resourceType": "Patient",
+                        "name": [{ "family": "Anyperson", "given": ["John"] }],
+                        "birthDate": "1951-01-20"
+                    }
+                },
+                {
+                    "fullUrl": "resource:1",
+                    "resource": {
+                        "resourceType": "Immunization",
+                        "status": "completed",
+                        "vaccineCode": {
+                            "coding": [{ "system": "http://hl7.org/fhir/sid/cvx", "code": "207" }]
+                        },
+                        "patient": { "reference": "resource:0" },
+                        "occurrenceDateTime": "2021-01-01",
+                        "performer": [{ "actor": { "display": "ABC General Hospital" } }],
+                        "lotNumber": "0000001"
+                    }
+                }
+            ]
+        });
+        let immunizations = get_immunizations(&bundle).unwrap();
+        assert_eq!(immunizations.len(), 1);
+        assert_eq!(
+            immunizations[0].vaccine_code[0].code,
+            Some("207".to_owned())
+        );
+        assert_eq!(
+            immunizations[0].performer,
+            Some("ABC General Hospital".to_owned())
+        );
+        assert!(get_immunizations(&serde_json::json!({})).is_err());
+    }
+}
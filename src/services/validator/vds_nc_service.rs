@@ -0,0 +1,541 @@
+use crate::{
+    dto::response::{
+        hc1_response_dto::{
+            CheckCode, CheckStatus, CheckType, CodeSystem, Vaccination, VerificationCheck,
+        },
+        vds_nc_response_dto::VdsNcValidationResponseDto,
+    },
+    responses::{
+        error_message::ErrorMessage, generic_response::Responses, success_messages::SuccessMessage,
+    },
+    services::{
+        disclosure::disclosure_profile::DisclosureProfile,
+        validator::certificate_service::get_pem_keys_by_country_and_kid,
+        x509::csca_trust_store::{CscaTrustStore, DscChainStatus},
+    },
+};
+use base64::{engine::general_purpose, Engine};
+use chrono::NaiveDate;
+use lacpass_verifier::{country_code::normalize_country_code, x509::X509Utils};
+use log::{debug, info};
+use openssl::{
+    asn1::{Asn1Time, Asn1TimeRef},
+    bn::BigNum,
+    ec::EcKeyRef,
+    ecdsa::EcdsaSig,
+    pkey::Public,
+    sha::{sha256, sha384, sha512},
+    x509::{X509Ref, X509},
+};
+use rocket::serde::json::Json;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use uuid::Uuid;
+
+// reference: ICAO Doc 9303 - Visible Digital Seals for Non-Constrained environments (VDS-NC)
+const VDS_NC_VACCINATION_TYPE: &str = "icao.vacc";
+const ICD_11_SYSTEM: &str = "http://id.who.int/icd/release/11/mms";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNc {
+    pub data: serde_json::Value,
+    pub sig: VdsNcSignature,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcSignature {
+    pub alg: String,
+    /// signer certificate, DER base64url encoded
+    pub cer: String,
+    /// signature value (`r || s`), base64url encoded
+    pub sigvl: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcData {
+    pub hdr: VdsNcHeader,
+    pub msg: VdsNcMessage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcHeader {
+    pub t: String,
+    /// issuing state, iso alpha3
+    pub is: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcMessage {
+    pub uvci: Option<String>,
+    pub pid: Option<VdsNcPerson>,
+    pub ve: Vec<VdsNcVaccine>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcPerson {
+    pub n: Option<String>,
+    pub dob: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcVaccine {
+    /// vaccine or prophylaxis (ICD-11)
+    pub des: String,
+    /// brand name
+    pub nam: String,
+    /// disease or agent targeted (ICD-11)
+    pub dis: String,
+    pub vd: Vec<VdsNcVaccinationDetail>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VdsNcVaccinationDetail {
+    pub dvc: String,
+    pub seq: u8,
+    pub ctr: String,
+    pub adm: Option<String>,
+    pub lot: String,
+    pub dvn: Option<String>,
+}
+
+/// Canonical JSON form of the signed `data`: object members sorted by name, no whitespaces
+pub fn canonicalize(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(members) => {
+            let mut names = members.keys().collect::<Vec<_>>();
+            names.sort();
+            let members = names
+                .into_iter()
+                .map(|name| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::String(name.to_owned()),
+                        canonicalize(&members[name])
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("{{{}}}", members.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items = items.iter().map(canonicalize).collect::<Vec<_>>();
+            format!("[{}]", items.join(","))
+        }
+        v => v.to_string(),
+    }
+}
+
+/// Verifies an ECDSA signature (`r || s`) over `data`; the digest is chosen by `alg`
+pub fn verify_ecdsa_signature(
+    public_key: &EcKeyRef<Public>,
+    alg: &str,
+    data: &[u8],
+    signature: &[u8],
+) -> anyhow::Result<bool> {
+    let digest = match alg {
+        "ES256" => sha256(data).to_vec(),
+        "ES384" => sha384(data).to_vec(),
+        "ES512" => sha512(data).to_vec(),
+        _ => {
+            let message = format!("Unsupported algorithm: {}", alg);
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    };
+    if signature.is_empty() || signature.len() % 2 != 0 {
+        return Ok(false);
+    }
+    let (r, s) = signature.split_at(signature.len() / 2);
+    let ecdsa_sig =
+        EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)?;
+    Ok(ecdsa_sig.verify(&digest, public_key)?)
+}
+
+/// One `Vaccination` per vaccination event
+pub fn get_vaccinations(message: &VdsNcMessage) -> Vec<Vaccination> {
+    message
+        .ve
+        .iter()
+        .flat_map(|vaccine| {
+            vaccine.vd.iter().map(move |detail| Vaccination {
+                date: detail.dvc.clone(),
+                dose: detail.seq,
+                vaccine: CodeSystem {
+                    code: Some(vaccine.des.clone()),
+                    system: Some(ICD_11_SYSTEM.to_owned()),
+                },
+                country: CodeSystem {
+                    code: Some(detail.ctr.clone()),
+                    system: None,
+                },
+                maholder: None,
+                lot: detail.lot.clone(),
+                centre: detail.adm.clone(),
+                brand: CodeSystem {
+                    code: Some(vaccine.nam.clone()),
+                    system: None,
+                },
+                manufacturer: None,
+                valid_from: None,
+                total_doses: None,
+                practitioner: None,
+                disease: Some(CodeSystem {
+                    code: Some(vaccine.dis.clone()),
+                    system: Some(ICD_11_SYSTEM.to_owned()),
+                }),
+                next_dose: detail.dvn.clone(),
+                extensions: Default::default(),
+            })
+        })
+        .collect::<Vec<_>>()
+}
+
+/// The signer certificate is trusted when it is registered in the trust list for the issuing country
+async fn is_trusted_certificate(
+    db: &DatabaseConnection,
+    country_code: &str,
+    kid: &str,
+    der: &[u8],
+) -> anyhow::Result<bool> {
+    Ok(get_pem_keys_by_country_and_kid(db, country_code, kid)
+        .await?
+        .into_iter()
+        // the seal carries no issuance time, compromised keys are not trusted
+        .filter(|trusted_key| trusted_key.is_usable_at(None))
+        .any(|trusted_key| {
+            X509Utils::get_decoded_pem_bytes(trusted_key.pem)
+                .map(|trusted_der| trusted_der == der)
+                .unwrap_or(false)
+        }))
+}
+
+fn get_unix_time(time: &Asn1TimeRef) -> anyhow::Result<i64> {
+    let diff = Asn1Time::from_unix(0)?.diff(time)?;
+    Ok(diff.days as i64 * 24 * 60 * 60 + diff.secs as i64)
+}
+
+/// The seal carries no signing time; it was signed on or after the last vaccination event
+fn get_earliest_signing_time(message: &VdsNcMessage) -> Option<i64> {
+    message
+        .ve
+        .iter()
+        .flat_map(|vaccine| vaccine.vd.iter())
+        .filter_map(|detail| {
+            detail
+                .dvc
+                .get(..10)
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.timestamp())
+        })
+        .max()
+}
+
+/// The signer certificate must be valid now and must not have expired before the seal could be signed
+pub fn get_key_expiry_check(
+    certificate: &X509Ref,
+    message: &VdsNcMessage,
+    now: i64,
+) -> VerificationCheck {
+    let (not_before, not_after) = match (
+        get_unix_time(certificate.not_before()),
+        get_unix_time(certificate.not_after()),
+    ) {
+        (Ok(not_before), Ok(not_after)) => (not_before, not_after),
+        _ => {
+            return VerificationCheck::failed(
+                CheckType::KeyExpiry,
+                CheckCode::InvalidCertificate,
+                "Invalid signer certificate validity",
+            )
+        }
+    };
+    if not_before > now {
+        return VerificationCheck::failed(
+            CheckType::KeyExpiry,
+            CheckCode::InvalidCertificate,
+            "Signer certificate is not valid yet",
+        );
+    }
+    match get_earliest_signing_time(message) {
+        Some(signed_at) if signed_at > not_after => VerificationCheck::failed(
+            CheckType::KeyExpiry,
+            CheckCode::SignedAfterKeyExpiry,
+            &format!(
+                "Signer certificate expired ({}) before the last vaccination ({})",
+                not_after, signed_at
+            ),
+        ),
+        Some(_) => VerificationCheck::passed(CheckType::KeyExpiry),
+        None => VerificationCheck::skipped(CheckType::KeyExpiry, "No valid vaccination date"),
+    }
+}
+
+fn invalid_response(
+    checks: Vec<VerificationCheck>,
+    trace_id: Uuid,
+) -> Responses<Json<SuccessMessage<VdsNcValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+    debug!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, checks);
+    Responses::Sucess(Json::from(SuccessMessage {
+        data: VdsNcValidationResponseDto {
+            is_valid: false,
+            kid: None,
+            country: None,
+            checks,
+            uvci: None,
+            name: None,
+            birth_date: None,
+            vaccinations: vec![],
+            receipt: None,
+        },
+        trace_id: trace_id.to_string(),
+    }))
+}
+
+pub async fn verify_vds_nc(
+    db: &DatabaseConnection,
+    data: String,
+    disclosure: DisclosureProfile,
+) -> Responses<Json<SuccessMessage<VdsNcValidationResponseDto>>, Json<ErrorMessage<'static>>> {
+    let trace_id: Uuid = Uuid::new_v4();
+    info!("New VDS-NC verification request: {:?}", trace_id);
+    let mut checks = Vec::new();
+    let vds_nc: VdsNc = match serde_json::from_str(data.trim()) {
+        Ok(v) => v,
+        Err(e) => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Decoding,
+                CheckCode::InvalidJson,
+                &format!("Invalid VDS-NC document: {}", e),
+            ));
+            return invalid_response(checks, trace_id);
+        }
+    };
+    let certificate = match base64_url::decode(&vds_nc.sig.cer)
+        .ok()
+        .and_then(|der| X509::from_der(&der).ok().map(|x509| (der, x509)))
+    {
+        Some(v) => v,
+        None => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Decoding,
+                CheckCode::InvalidCertificate,
+                "Invalid signer certificate",
+            ));
+            return invalid_response(checks, trace_id);
+        }
+    };
+    let (der, x509) = certificate;
+    checks.push(VerificationCheck::passed(CheckType::Decoding));
+
+    let vds_nc_data: VdsNcData = match serde_json::from_value(vds_nc.data.clone()) {
+        Ok(v) if v.hdr.t == VDS_NC_VACCINATION_TYPE => {
+            checks.push(VerificationCheck::passed(CheckType::Schema));
+            v
+        }
+        Ok(v) => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Schema,
+                CheckCode::InvalidSchema,
+                &format!("Unsupported VDS-NC type: {}", v.hdr.t),
+            ));
+            return invalid_response(checks, trace_id);
+        }
+        Err(e) => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Schema,
+                CheckCode::InvalidSchema,
+                &e.to_string(),
+            ));
+            return invalid_response(checks, trace_id);
+        }
+    };
+
+    let country_code = match normalize_country_code(&vds_nc_data.hdr.is) {
+        Some(v) => v,
+        None => {
+            checks.push(VerificationCheck::failed(
+                CheckType::KeyTrust,
+                CheckCode::SignerCountryNotFound,
+                "issuing country code not found",
+            ));
+            return invalid_response(checks, trace_id);
+        }
+    };
+    let pem = general_purpose::STANDARD.encode(&der);
+    let kid = X509Utils::get_kid_string_from_pem(pem.clone()).ok();
+    // with a CSCA configured for the country the certificate must chain to it, otherwise it must
+    // be registered in the trust list
+    let key_trust_check = match CscaTrustStore::get().validate_dsc(&country_code, &pem) {
+        DscChainStatus::Trusted => VerificationCheck::passed(CheckType::KeyTrust),
+        DscChainStatus::Untrusted(e) => {
+            VerificationCheck::failed(CheckType::KeyTrust, CheckCode::KeyNotFound, &e.to_string())
+        }
+        DscChainStatus::NoTrustAnchor => {
+            let is_trusted = match &kid {
+                Some(kid) => match is_trusted_certificate(db, &country_code, kid, &der).await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        let message = "Internal Error while getting keys";
+                        debug!("TRACE_ID: {}, DESCRIPTION ({}): {:?}", trace_id, message, e);
+                        return Responses::BadRequest(Json::from(ErrorMessage {
+                            message,
+                            trace_id: trace_id.to_string(),
+                        }));
+                    }
+                },
+                None => false,
+            };
+            if is_trusted {
+                VerificationCheck::passed(CheckType::KeyTrust)
+            } else {
+                VerificationCheck::failed(
+                    CheckType::KeyTrust,
+                    CheckCode::KeyNotFound,
+                    "Signer certificate is not trusted for the issuing country",
+                )
+            }
+        }
+    };
+    checks.push(
+        key_trust_check
+            .with_kid(kid.clone())
+            .with_country(Some(country_code.clone())),
+    );
+
+    let signature_result = base64_url::decode(&vds_nc.sig.sigvl)
+        .map_err(|e| anyhow::anyhow!(e))
+        .and_then(|signature| {
+            let public_key = x509.public_key()?.ec_key()?;
+            verify_ecdsa_signature(
+                &public_key,
+                &vds_nc.sig.alg,
+                canonicalize(&vds_nc.data).as_bytes(),
+                &signature,
+            )
+        });
+    let signature_check = match signature_result {
+        Ok(true) => VerificationCheck::passed(CheckType::Signature),
+        Ok(false) => VerificationCheck::failed(
+            CheckType::Signature,
+            CheckCode::InvalidSignature,
+            "Signature does not match the signer certificate",
+        ),
+        Err(e) => VerificationCheck::failed(
+            CheckType::Signature,
+            CheckCode::InvalidSignature,
+            &e.to_string(),
+        ),
+    };
+    checks.push(signature_check.with_kid(kid.clone()));
+    let now = chrono::Utc::now().timestamp();
+    checks.push(get_key_expiry_check(&x509, &vds_nc_data.msg, now).with_kid(kid.clone()));
+
+    let is_valid = checks
+        .iter()
+        .all(|check| check.status != CheckStatus::Failed);
+    if !is_valid {
+        debug!("TRACE_ID: {}, DESCRIPTION: {:?}", trace_id, checks);
+    }
+    let pid = vds_nc_data.msg.pid.clone();
+    Responses::Sucess(Json::from(SuccessMessage {
+        data: disclosure.apply_vds_nc(VdsNcValidationResponseDto {
+            is_valid,
+            kid,
+            country: Some(country_code),
+            checks,
+            uvci: vds_nc_data.msg.uvci.clone(),
+            name: pid.as_ref().and_then(|pid| pid.n.clone()),
+            birth_date: pid.and_then(|pid| pid.dob),
+            vaccinations: get_vaccinations(&vds_nc_data.msg),
+            receipt: None,
+        }),
+        trace_id: trace_id.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lacpass_verifier::test_support::{build_certificate, ec_key, DAY};
+    use openssl::{ec::EcGroup, ec::EcKey, nid::Nid};
+
+    fn get_vds_nc_data() -> serde_json::Value {
+        serde_json::json!({
+            "msg": {
+                "uvci": "U32870",
+                "pid": { "n": "Smith Bill", "dob": "1990-01-02", "sex": "M", "i": "A1234567Z" },
+                "ve": [{
+                    "des": "XM68M6",
+                    "nam": "Comirnaty",
+                    "dis": "RA01.0",
+                    "vd": [
+                        { "dvc": "2021-03-03", "seq": 1, "ctr": "UTO", "adm": "RIVM", "lot": "VC35679", "dvn": "2021-03-24" },
+                        { "dvc": "2021-03-24", "seq": 2, "ctr": "UTO", "adm": "RIVM", "lot": "VC87540" }
+                    ]
+                }]
+            },
+            "hdr": { "t": "icao.vacc", "v": 1, "is": "UTO" }
+        })
+    }
+
+    #[test]
+    fn canonicalize_test() {
+        let value = serde_json::json!({ "b": [1, "x"], "a": { "d": true, "c": null } });
+        assert_eq!(
+            canonicalize(&value),
+            r#"{"a":{"c":null,"d":true},"b":[1,"x"]}"#
+        );
+    }
+
+    #[test]
+    fn get_vaccinations_test() {
+        let data: VdsNcData = serde_json::from_value(get_vds_nc_data()).unwrap();
+        let vaccinations = get_vaccinations(&data.msg);
+        assert_eq!(vaccinations.len(), 2);
+        assert_eq!(vaccinations[1].dose, 2);
+        assert_eq!(vaccinations[1].lot, "VC87540");
+        assert_eq!(vaccinations[0].brand.code, Some("Comirnaty".to_owned()));
+        assert_eq!(vaccinations[0].next_dose, Some("2021-03-24".to_owned()));
+    }
+
+    #[test]
+    fn verify_ecdsa_signature_test() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = EcKey::generate(&group).unwrap();
+        let public_key = EcKey::from_public_key(&group, key.public_key()).unwrap();
+        let data = canonicalize(&get_vds_nc_data());
+        let ecdsa_sig = EcdsaSig::sign(&sha256(data.as_bytes()), &key).unwrap();
+        let mut signature = ecdsa_sig.r().to_vec_padded(32).unwrap();
+        signature.extend(ecdsa_sig.s().to_vec_padded(32).unwrap());
+        assert!(verify_ecdsa_signature(&public_key, "ES256", data.as_bytes(), &signature).unwrap());
+        assert!(!verify_ecdsa_signature(&public_key, "ES256", b"{}", &signature).unwrap());
+        assert!(verify_ecdsa_signature(&public_key, "RS256", data.as_bytes(), &signature).is_err());
+    }
+
+    #[test]
+    fn get_key_expiry_check_test() {
+        let data: VdsNcData = serde_json::from_value(get_vds_nc_data()).unwrap();
+        let key = ec_key(Nid::X9_62_PRIME256V1);
+        // last vaccination on 2021-03-24
+        let not_before = 1577836800; // 2020-01-01
+        let now = 1696118400; // 2023-10-01
+
+        // expired since, but still valid when the seal was signed
+        let certificate = build_certificate(&key, "DSC-Test", None, not_before, 1640995200, vec![]);
+        let check = get_key_expiry_check(&certificate, &data.msg, now);
+        assert_eq!(check.status, CheckStatus::Passed);
+
+        // expired on 2021-01-01, before the last vaccination
+        let certificate = build_certificate(&key, "DSC-Test", None, not_before, 1609459200, vec![]);
+        let check = get_key_expiry_check(&certificate, &data.msg, now);
+        assert_eq!(check.status, CheckStatus::Failed);
+        assert_eq!(check.code, CheckCode::SignedAfterKeyExpiry);
+
+        // not valid yet
+        let certificate =
+            build_certificate(&key, "DSC-Test", None, now + DAY, now + 365 * DAY, vec![]);
+        let check = get_key_expiry_check(&certificate, &data.msg, now);
+        assert_eq!(check.status, CheckStatus::Failed);
+        assert_eq!(check.code, CheckCode::InvalidCertificate);
+    }
+}
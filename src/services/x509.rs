@@ -1 +1 @@
-pub mod x509_utils;
+pub mod csca_trust_store;
@@ -0,0 +1,406 @@
+use std::{collections::HashMap, fmt, fs};
+
+use clap::__macro_refs::once_cell::sync::OnceCell;
+use lacpass_verifier::{country_code::normalize_country_code, x509::X509Utils};
+use log::{debug, info};
+use openssl::{
+    asn1::Asn1Time,
+    x509::{X509VerifyResult, X509},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::utils::Utils;
+
+/// CSCAs loaded once from the file pointed by `CSCA_TRUST_STORE_FILE` (json or toml)
+static CSCA_TRUST_STORE: OnceCell<CscaTrustStore> = OnceCell::new();
+
+/// A Country Signing CA certificate as found in the trust store file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CscaEntry {
+    /// iso alpha2 or alpha3 country code
+    pub country: String,
+    pub pem: String,
+}
+
+/// Content of the trust store file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CscaFile {
+    pub cscas: Vec<CscaEntry>,
+}
+
+/// Reasons why a DSC does not chain to a trusted CSCA
+#[derive(Debug, Clone, PartialEq)]
+pub enum DscChainError {
+    InvalidCertificate,
+    NotYetValid,
+    IsCa,
+    MissingDigitalSignatureKeyUsage,
+    IssuerNotTrusted,
+    OutsideIssuerValidity,
+}
+
+impl fmt::Display for DscChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DscChainError::InvalidCertificate => write!(f, "Unable to parse the DSC"),
+            DscChainError::NotYetValid => write!(f, "DSC is not valid yet"),
+            DscChainError::IsCa => write!(f, "DSC basic constraints mark it as a CA"),
+            DscChainError::MissingDigitalSignatureKeyUsage => {
+                write!(f, "DSC key usage does not allow digital signatures")
+            }
+            DscChainError::IssuerNotTrusted => {
+                write!(f, "DSC was not signed by a trusted CSCA of its country")
+            }
+            DscChainError::OutsideIssuerValidity => {
+                write!(f, "DSC was issued outside the validity of its CSCA")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DscChainStatus {
+    /// signed by a trusted CSCA of the country
+    Trusted,
+    /// no CSCA configured for the country, the DSC cannot be evaluated
+    NoTrustAnchor,
+    Untrusted(DscChainError),
+}
+
+impl DscChainStatus {
+    /// Value stored in `public_key.is_chain_trusted`
+    pub fn is_trusted(&self) -> Option<bool> {
+        match self {
+            DscChainStatus::Trusted => Some(true),
+            DscChainStatus::NoTrustAnchor => None,
+            DscChainStatus::Untrusted(_) => Some(false),
+        }
+    }
+}
+
+/// CSCA certificates by iso alpha3 country code
+#[derive(Default)]
+pub struct CscaTrustStore {
+    cscas_by_country: HashMap<String, Vec<X509>>,
+}
+
+impl CscaTrustStore {
+    /// Builds the trust store; CSCAs that cannot be parsed or that are not CAs are left out
+    pub fn new(entries: Vec<CscaEntry>) -> Self {
+        let mut cscas_by_country: HashMap<String, Vec<X509>> = HashMap::new();
+        for entry in entries {
+            let country_code = match normalize_country_code(&entry.country) {
+                Some(v) => v,
+                None => {
+                    info!("Skipping CSCA with invalid country code {}", entry.country);
+                    continue;
+                }
+            };
+            let pem = X509Utils::format_pem(entry.pem);
+            let csca = match X509::from_pem(pem.as_bytes()) {
+                Ok(v) => v,
+                Err(e) => {
+                    info!(
+                        "Skipping invalid CSCA for country {}, error was: {:?}",
+                        country_code, e
+                    );
+                    continue;
+                }
+            };
+            if !Self::is_valid_csca(&csca) {
+                info!(
+                    "Skipping CSCA for country {}: basic constraints or key usage do not allow signing certificates",
+                    country_code
+                );
+                continue;
+            }
+            cscas_by_country.entry(country_code).or_default().push(csca);
+        }
+        CscaTrustStore { cscas_by_country }
+    }
+
+    pub fn from_file_content(path: &str, content: &str) -> anyhow::Result<Self> {
+        let file: CscaFile = if path.ends_with(".toml") {
+            toml::from_str(content)?
+        } else {
+            serde_json::from_str(content)?
+        };
+        Ok(Self::new(file.cscas))
+    }
+
+    /// Trust store loaded from `CSCA_TRUST_STORE_FILE`; empty when not configured
+    pub fn get() -> &'static CscaTrustStore {
+        CSCA_TRUST_STORE.get_or_init(|| {
+            let path = match Utils::get_env_or_err("CSCA_TRUST_STORE_FILE") {
+                Ok(v) => v,
+                Err(_) => return CscaTrustStore::default(),
+            };
+            let loaded = fs::read_to_string(&path)
+                .map_err(|e| anyhow::anyhow!(e))
+                .and_then(|content| Self::from_file_content(&path, &content));
+            match loaded {
+                Ok(v) => {
+                    info!(
+                        "Loaded CSCAs for {} countries from {}",
+                        v.cscas_by_country.len(),
+                        path
+                    );
+                    v
+                }
+                Err(e) => {
+                    info!(
+                        "Unable to load CSCA trust store {}, error was: {:?}",
+                        path, e
+                    );
+                    CscaTrustStore::default()
+                }
+            }
+        })
+    }
+
+    fn is_valid_csca(csca: &X509) -> bool {
+        let der = match csca.to_der() {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        let is_ca = X509Utils::is_ca(&der).unwrap_or(false);
+        let can_sign_certificates = match X509Utils::get_key_usage(&der) {
+            Ok(Some(key_usage)) => key_usage & X509Utils::KEY_USAGE_KEY_CERT_SIGN != 0,
+            Ok(None) => true,
+            Err(_) => false,
+        };
+        is_ca && can_sign_certificates
+    }
+
+    /// Checks that the DSC was signed by one of the country CSCAs, during the CSCA validity, and
+    /// that its basic constraints and key usage are those of a document signer
+    pub fn validate_dsc(&self, country_code: &str, pem: &str) -> DscChainStatus {
+        let cscas = match self.cscas_by_country.get(country_code) {
+            Some(v) if !v.is_empty() => v,
+            _ => return DscChainStatus::NoTrustAnchor,
+        };
+        match Self::check_dsc(cscas, pem) {
+            Ok(()) => DscChainStatus::Trusted,
+            Err(e) => {
+                debug!("DSC for country {} does not chain: {}", country_code, e);
+                DscChainStatus::Untrusted(e)
+            }
+        }
+    }
+
+    fn check_dsc(cscas: &[X509], pem: &str) -> Result<(), DscChainError> {
+        let pem = X509Utils::format_pem(pem.to_owned());
+        let dsc = X509::from_pem(pem.as_bytes()).map_err(|_| DscChainError::InvalidCertificate)?;
+        let der = dsc
+            .to_der()
+            .map_err(|_| DscChainError::InvalidCertificate)?;
+
+        let now = Asn1Time::days_from_now(0).map_err(|_| DscChainError::InvalidCertificate)?;
+        if *dsc.not_before() > *now {
+            return Err(DscChainError::NotYetValid);
+        }
+        if X509Utils::is_ca(&der).map_err(|_| DscChainError::InvalidCertificate)? {
+            return Err(DscChainError::IsCa);
+        }
+        match X509Utils::get_key_usage(&der) {
+            Ok(Some(key_usage)) if key_usage & X509Utils::KEY_USAGE_DIGITAL_SIGNATURE != 0 => {}
+            _ => return Err(DscChainError::MissingDigitalSignatureKeyUsage),
+        }
+
+        let issuers = cscas
+            .iter()
+            .filter(|csca| csca.issued(&dsc) == X509VerifyResult::OK)
+            .filter(|csca| match csca.public_key() {
+                Ok(key) => dsc.verify(&key).unwrap_or(false),
+                Err(_) => false,
+            })
+            .collect::<Vec<_>>();
+        if issuers.is_empty() {
+            return Err(DscChainError::IssuerNotTrusted);
+        }
+        // chain model: the DSC must have been issued while its CSCA was valid
+        let issued_while_valid = issuers.iter().any(|csca| {
+            dsc.not_before() >= csca.not_before() && dsc.not_before() <= csca.not_after()
+        });
+        if !issued_while_valid {
+            return Err(DscChainError::OutsideIssuerValidity);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lacpass_verifier::test_support::{self, ec_key, to_pem, DAY};
+    use openssl::{
+        nid::Nid,
+        pkey::{PKey, Private},
+        x509::extension::{BasicConstraints, KeyUsage},
+    };
+
+    struct TestCertificate {
+        cert: X509,
+        key: PKey<Private>,
+    }
+
+    fn build_certificate(
+        common_name: &str,
+        issuer: Option<&TestCertificate>,
+        is_ca: bool,
+        digital_signature: bool,
+        not_before: i64,
+        not_after: i64,
+    ) -> TestCertificate {
+        let key = ec_key(Nid::X9_62_PRIME256V1);
+        let extensions = if is_ca {
+            vec![
+                BasicConstraints::new().critical().ca().build().unwrap(),
+                KeyUsage::new().key_cert_sign().crl_sign().build().unwrap(),
+            ]
+        } else {
+            let mut key_usage = KeyUsage::new();
+            if digital_signature {
+                key_usage.digital_signature();
+            } else {
+                key_usage.key_encipherment();
+            }
+            vec![
+                BasicConstraints::new().build().unwrap(),
+                key_usage.build().unwrap(),
+            ]
+        };
+        let cert = test_support::build_certificate(
+            &key,
+            common_name,
+            issuer.map(|issuer| (&issuer.cert, &issuer.key)),
+            not_before,
+            not_after,
+            extensions,
+        );
+        TestCertificate { cert, key }
+    }
+
+    fn pem(certificate: &TestCertificate) -> String {
+        to_pem(&certificate.cert)
+    }
+
+    fn trust_store(csca: &TestCertificate) -> CscaTrustStore {
+        CscaTrustStore::new(vec![CscaEntry {
+            country: "CL".to_owned(),
+            pem: pem(csca),
+        }])
+    }
+
+    #[test]
+    fn validate_dsc_test() {
+        let now = chrono::Utc::now().timestamp();
+        let csca = build_certificate(
+            "CA-MoH",
+            None,
+            true,
+            false,
+            now - 100 * DAY,
+            now + 3650 * DAY,
+        );
+        let store = trust_store(&csca);
+
+        let dsc = build_certificate(
+            "DSC-MoH",
+            Some(&csca),
+            false,
+            true,
+            now - DAY,
+            now + 365 * DAY,
+        );
+        assert_eq!(
+            store.validate_dsc("CHL", &pem(&dsc)),
+            DscChainStatus::Trusted
+        );
+        assert_eq!(
+            store.validate_dsc("ARG", &pem(&dsc)),
+            DscChainStatus::NoTrustAnchor
+        );
+
+        let other_csca = build_certificate(
+            "CA-MoH",
+            None,
+            true,
+            false,
+            now - 100 * DAY,
+            now + 3650 * DAY,
+        );
+        let foreign_dsc = build_certificate(
+            "DSC-MoH",
+            Some(&other_csca),
+            false,
+            true,
+            now - DAY,
+            now + 365 * DAY,
+        );
+        assert_eq!(
+            store.validate_dsc("CHL", &pem(&foreign_dsc)),
+            DscChainStatus::Untrusted(DscChainError::IssuerNotTrusted)
+        );
+
+        let ca_dsc = build_certificate(
+            "DSC-MoH",
+            Some(&csca),
+            true,
+            false,
+            now - DAY,
+            now + 365 * DAY,
+        );
+        assert_eq!(
+            store.validate_dsc("CHL", &pem(&ca_dsc)),
+            DscChainStatus::Untrusted(DscChainError::IsCa)
+        );
+
+        let encipherment_dsc = build_certificate(
+            "DSC-MoH",
+            Some(&csca),
+            false,
+            false,
+            now - DAY,
+            now + 365 * DAY,
+        );
+        assert_eq!(
+            store.validate_dsc("CHL", &pem(&encipherment_dsc)),
+            DscChainStatus::Untrusted(DscChainError::MissingDigitalSignatureKeyUsage)
+        );
+
+        let early_dsc = build_certificate(
+            "DSC-MoH",
+            Some(&csca),
+            false,
+            true,
+            now - 200 * DAY,
+            now + 365 * DAY,
+        );
+        assert_eq!(
+            store.validate_dsc("CHL", &pem(&early_dsc)),
+            DscChainStatus::Untrusted(DscChainError::OutsideIssuerValidity)
+        );
+
+        let future_dsc = build_certificate(
+            "DSC-MoH",
+            Some(&csca),
+            false,
+            true,
+            now + DAY,
+            now + 365 * DAY,
+        );
+        assert_eq!(
+            store.validate_dsc("CHL", &pem(&future_dsc)),
+            DscChainStatus::Untrusted(DscChainError::NotYetValid)
+        );
+    }
+
+    #[test]
+    fn csca_must_be_a_ca_test() {
+        let now = chrono::Utc::now().timestamp();
+        let not_a_ca = build_certificate("CA-MoH", None, false, true, now - DAY, now + 365 * DAY);
+        let store = trust_store(&not_a_ca);
+        assert!(store.cscas_by_country.is_empty());
+    }
+}
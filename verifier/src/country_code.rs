@@ -759,3 +759,31 @@ pub static ALPHA2_TO_ALPHA3: phf::Map<&'static str, &'static str> = phf_map! {
     "ZW" => "ZWE",
     "AX" => "ALA",
 };
+
+/// Returns the alpha3 form of an alpha2 or alpha3 country code, in any case
+pub fn normalize_country_code(country_code: &str) -> Option<String> {
+    let country_code = country_code.to_uppercase();
+    if ALPHA3_TO_ALPHA2.contains_key(&country_code) {
+        return Some(country_code);
+    }
+    // the testing entries ("LP", "LAC") do not map to a country
+    ALPHA2_TO_ALPHA3
+        .get(&country_code)
+        .filter(|alpha3| ALPHA3_TO_ALPHA2.contains_key(**alpha3))
+        .map(|alpha3| alpha3.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_country_code_test() {
+        assert_eq!(normalize_country_code("cl"), Some("CHL".to_owned()));
+        assert_eq!(normalize_country_code("CHL"), Some("CHL".to_owned()));
+        assert_eq!(normalize_country_code("arg"), Some("ARG".to_owned()));
+        assert_eq!(normalize_country_code("LP"), None);
+        assert_eq!(normalize_country_code("LAC"), None);
+        assert_eq!(normalize_country_code("XX"), None);
+    }
+}
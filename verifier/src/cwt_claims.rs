@@ -0,0 +1,113 @@
+use ciborium::value::Value;
+use log::debug;
+
+/// CWT claim keys (reference: https://www.rfc-editor.org/rfc/rfc8392#section-3.1)
+pub const CWT_ISS: i128 = 1;
+pub const CWT_EXP: i128 = 4;
+pub const CWT_IAT: i128 = 6;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CwtClaims {
+    pub iss: Option<String>,
+    pub exp: Option<i64>,
+    pub iat: Option<i64>,
+}
+
+impl CwtClaims {
+    /// Extracts iss (1), exp (4) and iat (6) from a CWT payload; absent claims are returned as `None`
+    pub fn from_payload(payload: &[u8]) -> anyhow::Result<CwtClaims> {
+        let value: Value = match ciborium::de::from_reader(payload) {
+            Ok(v) => v,
+            Err(e) => {
+                let message = format!("Unable to decode CWT payload, error was: {:?}", e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        };
+        let claims = match Self::untag(value) {
+            Value::Map(m) => m,
+            _ => {
+                let message = "CWT payload is not a map";
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        };
+        let iss = match Self::get_claim(&claims, CWT_ISS) {
+            Some(Value::Text(iss)) => Some(iss.to_owned()),
+            _ => None,
+        };
+        let exp = Self::get_claim(&claims, CWT_EXP).and_then(Self::get_numeric_date);
+        let iat = Self::get_claim(&claims, CWT_IAT).and_then(Self::get_numeric_date);
+        Ok(CwtClaims { iss, exp, iat })
+    }
+
+    pub fn get_claim(claims: &[(Value, Value)], claim_key: i128) -> Option<&Value> {
+        claims.iter().find_map(|(k, v)| match k {
+            Value::Integer(i) if i128::from(*i) == claim_key => Some(v),
+            _ => None,
+        })
+    }
+
+    /// NumericDate may come as an integer or as a floating point value
+    fn get_numeric_date(value: &Value) -> Option<i64> {
+        match value {
+            Value::Integer(i) => i64::try_from(*i).ok(),
+            Value::Float(f) => Some(*f as i64),
+            _ => None,
+        }
+    }
+
+    /// CWT payloads may be wrapped in the CWT tag (61)
+    pub fn untag(value: Value) -> Value {
+        match value {
+            Value::Tag(_, inner) => Self::untag(*inner),
+            v => v,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn from_payload_test() {
+        let payload = encode(Value::Map(vec![
+            (Value::Integer(1.into()), Value::Text("CHL".to_owned())),
+            (Value::Integer(4.into()), Value::Integer(1735689600.into())),
+            (Value::Integer(6.into()), Value::Float(1704067200.0)),
+            (Value::Integer((-260).into()), Value::Map(vec![])),
+        ]));
+        let claims = CwtClaims::from_payload(&payload).unwrap();
+        assert_eq!(
+            claims,
+            CwtClaims {
+                iss: Some("CHL".to_owned()),
+                exp: Some(1735689600),
+                iat: Some(1704067200),
+            }
+        );
+    }
+
+    #[test]
+    fn from_payload_missing_claims_test() {
+        let payload = encode(Value::Map(vec![(
+            Value::Integer((-260).into()),
+            Value::Map(vec![]),
+        )]));
+        let claims = CwtClaims::from_payload(&payload).unwrap();
+        assert_eq!(claims, CwtClaims::default());
+    }
+
+    #[test]
+    fn from_payload_not_a_map_test() {
+        let payload = encode(Value::Text("HC1".to_owned()));
+        assert!(CwtClaims::from_payload(&payload).is_err());
+    }
+}
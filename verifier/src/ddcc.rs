@@ -0,0 +1,512 @@
+use std::collections::BTreeMap;
+
+use ciborium::value::Value;
+use log::debug;
+
+use crate::{
+    cwt_claims::CwtClaims,
+    eu_dcc::HCERT_CLAIM,
+    types::hc1::{
+        Certificate, CodeSystem, DdccCoreDataSet, Identifier, Period, Recovery, TestResult,
+        Vaccination, Value as ValueDto,
+    },
+};
+
+/// key of the WHO DDCC core data set inside the hcert claim
+/// (reference: https://worldhealthorganization.github.io/ddcc/)
+pub const DDCC_CLAIM: i128 = -6;
+
+/// Returns the DDCC core data set carried in the hcert claim (-260 / -6) of a CWT payload;
+/// `None` if the payload has no DDCC claim. Errors name the path of the offending field
+pub fn get_ddcc_struct(payload: &[u8]) -> anyhow::Result<Option<DdccCoreDataSet>> {
+    let value: Value = match ciborium::de::from_reader(payload) {
+        Ok(v) => v,
+        Err(e) => {
+            let message = format!("Unable to decode CWT payload, error was: {:?}", e);
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    };
+    let claims = match CwtClaims::untag(value) {
+        Value::Map(m) => m,
+        _ => return Ok(None),
+    };
+    let hcert = match CwtClaims::get_claim(&claims, HCERT_CLAIM) {
+        Some(Value::Map(hcert)) => hcert,
+        _ => return Ok(None),
+    };
+    let ddcc_value = match CwtClaims::get_claim(hcert, DDCC_CLAIM) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    match get_ddcc_core_data_set(ddcc_value) {
+        Ok(v) => Ok(Some(v)),
+        Err(e) => {
+            let message = format!("Invalid DDCC payload, error was: {}", e);
+            debug!("{}", message);
+            Err(anyhow::anyhow!(message))
+        }
+    }
+}
+
+/// Text keyed CBOR map being read field by field; the fields that were not read are kept as
+/// extensions
+struct DdccMap<'a> {
+    path: String,
+    entries: Vec<(&'a str, &'a Value)>,
+    read: Vec<&'static str>,
+}
+
+impl<'a> DdccMap<'a> {
+    fn new(value: &'a Value, path: String) -> anyhow::Result<Self> {
+        let map = match untag(value) {
+            Value::Map(m) => m,
+            _ => return Err(anyhow::anyhow!("'{}' is not a map", path)),
+        };
+        let mut entries = Vec::with_capacity(map.len());
+        for (k, v) in map {
+            match k {
+                Value::Text(k) => entries.push((k.as_str(), v)),
+                k => return Err(anyhow::anyhow!("'{}' has a non text key {:?}", path, k)),
+            }
+        }
+        Ok(DdccMap {
+            path,
+            entries,
+            read: vec![],
+        })
+    }
+
+    fn child_path(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}.{}", self.path, name)
+        }
+    }
+
+    /// Null values are handled as absent fields
+    fn get(&mut self, name: &'static str) -> Option<&'a Value> {
+        self.read.push(name);
+        self.entries
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| untag(*v))
+            .filter(|v| !v.is_null())
+    }
+
+    fn missing(&self, name: &str) -> anyhow::Error {
+        anyhow::anyhow!("'{}' is missing", self.child_path(name))
+    }
+
+    fn optional_string(&mut self, name: &'static str) -> anyhow::Result<Option<String>> {
+        match self.get(name) {
+            Some(Value::Text(v)) => Ok(Some(v.to_owned())),
+            Some(_) => Err(anyhow::anyhow!(
+                "'{}' is not a string",
+                self.child_path(name)
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn required_string(&mut self, name: &'static str) -> anyhow::Result<String> {
+        self.optional_string(name)?
+            .ok_or_else(|| self.missing(name))
+    }
+
+    /// Any integer encoding is accepted as long as the value fits
+    fn optional_u8(&mut self, name: &'static str) -> anyhow::Result<Option<u8>> {
+        match self.get(name) {
+            Some(Value::Integer(v)) => match u8::try_from(i128::from(*v)) {
+                Ok(v) => Ok(Some(v)),
+                Err(_) => Err(anyhow::anyhow!(
+                    "'{}' is out of range (0 to 255): {}",
+                    self.child_path(name),
+                    i128::from(*v)
+                )),
+            },
+            Some(_) => Err(anyhow::anyhow!(
+                "'{}' is not an integer",
+                self.child_path(name)
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn required_u8(&mut self, name: &'static str) -> anyhow::Result<u8> {
+        self.optional_u8(name)?.ok_or_else(|| self.missing(name))
+    }
+
+    fn optional_map(&mut self, name: &'static str) -> anyhow::Result<Option<DdccMap<'a>>> {
+        match self.get(name) {
+            Some(v) => Ok(Some(DdccMap::new(v, self.child_path(name))?)),
+            None => Ok(None),
+        }
+    }
+
+    fn required_map(&mut self, name: &'static str) -> anyhow::Result<DdccMap<'a>> {
+        self.optional_map(name)?.ok_or_else(|| self.missing(name))
+    }
+
+    fn optional_code_system(&mut self, name: &'static str) -> anyhow::Result<Option<CodeSystem>> {
+        self.optional_map(name)?.map(get_code_system).transpose()
+    }
+
+    fn required_code_system(&mut self, name: &'static str) -> anyhow::Result<CodeSystem> {
+        get_code_system(self.required_map(name)?)
+    }
+
+    fn optional_value(&mut self, name: &'static str) -> anyhow::Result<Option<ValueDto>> {
+        self.optional_map(name)?.map(get_value).transpose()
+    }
+
+    /// Fields that were not read, as json
+    fn into_extensions(self) -> BTreeMap<String, serde_json::Value> {
+        let read = self.read;
+        self.entries
+            .into_iter()
+            .filter(|(k, _)| !read.iter().any(|r| r == k))
+            .map(|(k, v)| (k.to_owned(), cbor_to_json(v)))
+            .collect()
+    }
+}
+
+fn untag(value: &Value) -> &Value {
+    match value {
+        Value::Tag(_, inner) => untag(inner),
+        v => v,
+    }
+}
+
+/// Byte strings are returned base64url encoded; non text map keys as their debug form
+fn cbor_to_json(value: &Value) -> serde_json::Value {
+    match untag(value) {
+        Value::Integer(i) => {
+            let i = i128::from(*i);
+            match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(v), _) => serde_json::Value::from(v),
+                (_, Ok(v)) => serde_json::Value::from(v),
+                _ => serde_json::Value::from(i.to_string()),
+            }
+        }
+        Value::Bytes(b) => serde_json::Value::from(base64_url::encode(b)),
+        Value::Float(f) => serde_json::Value::from(*f),
+        Value::Text(t) => serde_json::Value::from(t.to_owned()),
+        Value::Bool(b) => serde_json::Value::from(*b),
+        Value::Array(a) => serde_json::Value::Array(a.iter().map(cbor_to_json).collect()),
+        Value::Map(m) => serde_json::Value::Object(
+            m.iter()
+                .map(|(k, v)| {
+                    let k = match k {
+                        Value::Text(t) => t.to_owned(),
+                        k => format!("{:?}", k),
+                    };
+                    (k, cbor_to_json(v))
+                })
+                .collect(),
+        ),
+        _ => serde_json::Value::Null,
+    }
+}
+
+fn get_code_system(mut map: DdccMap) -> anyhow::Result<CodeSystem> {
+    Ok(CodeSystem {
+        code: map.optional_string("code")?,
+        system: map.optional_string("system")?,
+    })
+}
+
+fn get_value(mut map: DdccMap) -> anyhow::Result<ValueDto> {
+    Ok(ValueDto {
+        value: map.optional_string("value")?,
+    })
+}
+
+// reference: https://worldhealthorganization.github.io/ddcc/StructureDefinition-DDCCCoreDataSet.VS.html
+fn get_vaccination(mut map: DdccMap) -> anyhow::Result<Vaccination> {
+    Ok(Vaccination {
+        date: map.required_string("date")?,
+        dose: map.required_u8("dose")?,
+        vaccine: map.required_code_system("vaccine")?,
+        country: map.required_code_system("country")?,
+        maholder: map.optional_code_system("maholder")?,
+        lot: map.required_string("lot")?,
+        centre: map.optional_string("centre")?,
+        brand: map.required_code_system("brand")?,
+        manufacturer: map.optional_code_system("manufacturer")?,
+        valid_from: map.optional_string("validFrom")?,
+        total_doses: map.optional_u8("totalDoses")?,
+        practitioner: map.optional_value("practitioner")?,
+        disease: map.optional_code_system("disease")?,
+        next_dose: map.optional_string("nextDose")?,
+        extensions: map.into_extensions(),
+    })
+}
+
+// reference: https://worldhealthorganization.github.io/ddcc/StructureDefinition-DDCCCoreDataSet.TR.html
+fn get_test_result(mut map: DdccMap) -> anyhow::Result<TestResult> {
+    Ok(TestResult {
+        pathogen: map.required_code_system("pathogen")?,
+        test_type: map.required_code_system("type")?,
+        brand: map.optional_code_system("brand")?,
+        manufacturer: map.optional_code_system("manufacturer")?,
+        origin: map.optional_code_system("origin")?,
+        date: map.required_string("date")?,
+        result: map.required_code_system("result")?,
+        centre: map.optional_string("centre")?,
+        country: map.optional_code_system("country")?,
+        extensions: map.into_extensions(),
+    })
+}
+
+fn get_recovery(mut map: DdccMap) -> anyhow::Result<Recovery> {
+    Ok(Recovery {
+        disease: map.optional_code_system("disease")?,
+        date: map.required_string("date")?,
+        valid_from: map.optional_string("validFrom")?,
+        valid_until: map.optional_string("validUntil")?,
+        country: map.optional_code_system("country")?,
+        centre: map.optional_string("centre")?,
+        extensions: map.into_extensions(),
+    })
+}
+
+fn get_certificate(mut map: DdccMap) -> anyhow::Result<Certificate> {
+    let hcid = get_value(map.required_map("hcid")?)?;
+    let mut issuer = map.required_map("issuer")?;
+    let issuer = Identifier {
+        identifier: match issuer.optional_value("identifier")? {
+            Some(v) => v,
+            None => ValueDto { value: None },
+        },
+    };
+    let period = match map.optional_map("period")? {
+        Some(mut period) => Some(Period {
+            start: period.optional_string("start")?,
+            end: period.optional_string("end")?,
+        }),
+        None => None,
+    };
+    Ok(Certificate {
+        hcid,
+        period,
+        issuer,
+        version: map.required_string("version")?,
+        extensions: map.into_extensions(),
+    })
+}
+
+fn get_ddcc_core_data_set(value: &Value) -> anyhow::Result<DdccCoreDataSet> {
+    let mut map = DdccMap::new(value, String::new())?;
+    let vaccination = map.optional_map("vaccination")?.map(get_vaccination);
+    let test_result = map.optional_map("test")?.map(get_test_result);
+    let recovery = map.optional_map("recovery")?.map(get_recovery);
+    let (vaccination, test_result, recovery) = (
+        vaccination.transpose()?,
+        test_result.transpose()?,
+        recovery.transpose()?,
+    );
+    if vaccination.is_none() && test_result.is_none() && recovery.is_none() {
+        return Err(anyhow::anyhow!(
+            "No 'vaccination', 'test' or 'recovery' map found"
+        ));
+    }
+    Ok(DdccCoreDataSet {
+        vaccination,
+        test_result,
+        recovery,
+        resource_type: map.optional_string("resourceType")?,
+        birth_date: map.optional_string("birthDate")?,
+        name: map.required_string("name")?,
+        identifier: map.optional_string("identifier")?,
+        sex: map.optional_string("sex")?,
+        certificate: map
+            .optional_map("certificate")?
+            .map(get_certificate)
+            .transpose()?,
+        extensions: map.into_extensions(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_owned())
+    }
+
+    fn map(entries: Vec<(&str, Value)>) -> Value {
+        Value::Map(entries.into_iter().map(|(k, v)| (text(k), v)).collect())
+    }
+
+    fn code(code: &str) -> Value {
+        map(vec![("code", text(code))])
+    }
+
+    fn ddcc_payload(ddcc: Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(
+            &Value::Map(vec![
+                (Value::Integer(1.into()), text("CHL")),
+                (
+                    Value::Integer((-260).into()),
+                    Value::Map(vec![(Value::Integer((-6).into()), ddcc)]),
+                ),
+            ]),
+            &mut bytes,
+        )
+        .unwrap();
+        bytes
+    }
+
+    fn vaccination(dose: Value) -> Value {
+        map(vec![
+            ("date", text("2023-09-01")),
+            ("dose", dose),
+            ("vaccine", code("XM68M6")),
+            ("country", code("CHL")),
+            ("brand", code("XM8NQ0")),
+            ("lot", text("VC35679")),
+            ("totalDoses", Value::Integer(2.into())),
+            ("vaccinationSite", text("left arm")),
+        ])
+    }
+
+    #[test]
+    fn get_ddcc_vaccination_test() {
+        let payload = ddcc_payload(map(vec![
+            ("name", text("Jane Doe")),
+            ("birthDate", text("1990-01-01")),
+            (
+                "certificate",
+                map(vec![
+                    ("hcid", map(vec![("value", text("123456"))])),
+                    (
+                        "issuer",
+                        map(vec![("identifier", map(vec![("value", text("MoH"))]))]),
+                    ),
+                    ("version", text("RC-2.0.0")),
+                ]),
+            ),
+            ("vaccination", vaccination(Value::Integer(1.into()))),
+            ("nationality", text("CHL")),
+        ]));
+        let ddcc = get_ddcc_struct(&payload).unwrap().unwrap();
+        assert_eq!(ddcc.name, "Jane Doe");
+        assert_eq!(
+            ddcc.certificate.unwrap().issuer.identifier.value.as_deref(),
+            Some("MoH")
+        );
+        let vaccination = ddcc.vaccination.unwrap();
+        assert_eq!(vaccination.dose, 1);
+        assert_eq!(vaccination.total_doses, Some(2));
+        assert_eq!(
+            vaccination.extensions.get("vaccinationSite"),
+            Some(&serde_json::Value::from("left arm"))
+        );
+        assert_eq!(
+            ddcc.extensions.get("nationality"),
+            Some(&serde_json::Value::from("CHL"))
+        );
+    }
+
+    #[test]
+    fn get_ddcc_dose_encoding_test() {
+        // a dose of 1 encoded on 16 bits is still a valid dose
+        let mut payload = ddcc_payload(map(vec![
+            ("name", text("Jane Doe")),
+            ("vaccination", vaccination(Value::Integer(1.into()))),
+        ]));
+        let position = payload.windows(5).position(|w| w == b"dose\x01").unwrap();
+        payload.splice(position + 4..position + 5, [0x19, 0x00, 0x01]);
+        let ddcc = get_ddcc_struct(&payload).unwrap().unwrap();
+        assert_eq!(ddcc.vaccination.unwrap().dose, 1);
+
+        let payload = ddcc_payload(map(vec![
+            ("name", text("Jane Doe")),
+            ("vaccination", vaccination(Value::Integer(300.into()))),
+        ]));
+        let error = get_ddcc_struct(&payload).unwrap_err().to_string();
+        assert!(error.contains("'vaccination.dose' is out of range"));
+    }
+
+    #[test]
+    fn get_ddcc_with_test_result_test() {
+        let payload = ddcc_payload(map(vec![
+            ("name", text("Jane Doe")),
+            (
+                "test",
+                map(vec![
+                    ("pathogen", code("840539006")),
+                    ("type", code("LP6464-4")),
+                    ("result", code("260415000")),
+                    ("date", text("2023-10-01T10:00:00Z")),
+                ]),
+            ),
+        ]));
+        let ddcc = get_ddcc_struct(&payload).unwrap().unwrap();
+        assert!(ddcc.vaccination.is_none());
+        assert!(ddcc.recovery.is_none());
+        let test_result = ddcc.test_result.unwrap();
+        assert_eq!(test_result.test_type.code.as_deref(), Some("LP6464-4"));
+    }
+
+    #[test]
+    fn get_ddcc_errors_test() {
+        let cases = vec![
+            (
+                map(vec![("name", text("Jane Doe"))]),
+                "No 'vaccination', 'test' or 'recovery' map found",
+            ),
+            (
+                map(vec![(
+                    "test",
+                    map(vec![
+                        ("pathogen", code("840539006")),
+                        ("type", code("LP6464-4")),
+                        ("date", text("2023-10-01T10:00:00Z")),
+                    ]),
+                )]),
+                "'test.result' is missing",
+            ),
+            (
+                map(vec![("vaccination", vaccination(text("1")))]),
+                "'vaccination.dose' is not an integer",
+            ),
+            (
+                map(vec![
+                    ("name", text("Jane Doe")),
+                    ("recovery", map(vec![("date", text("2023-10-01"))])),
+                    ("certificate", map(vec![("hcid", text("123456"))])),
+                ]),
+                "'certificate.hcid' is not a map",
+            ),
+            (
+                map(vec![("recovery", map(vec![("date", text("2023-10-01"))]))]),
+                "'name' is missing",
+            ),
+        ];
+        for (ddcc, expected) in cases {
+            let error = get_ddcc_struct(&ddcc_payload(ddcc))
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains(expected), "{} not in {}", expected, error);
+        }
+    }
+
+    #[test]
+    fn get_ddcc_not_a_ddcc_test() {
+        let mut payload = Vec::new();
+        ciborium::ser::into_writer(
+            &Value::Map(vec![(
+                Value::Integer((-260).into()),
+                Value::Map(vec![(Value::Integer(1.into()), Value::Map(vec![]))]),
+            )]),
+            &mut payload,
+        )
+        .unwrap();
+        assert!(get_ddcc_struct(&payload).unwrap().is_none());
+    }
+}
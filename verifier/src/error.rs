@@ -0,0 +1,68 @@
+use std::fmt;
+
+use crate::types::hc1::{CheckCode, CheckType};
+
+#[derive(Debug, Clone)]
+pub enum CertificateError {
+    INVALID,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertificateError::INVALID => {
+                write!(f, "Invalid EC public key input")
+            }
+        }
+    }
+}
+
+/// Reasons why a health certificate with a valid signature is still rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationError {
+    PayloadExpired { exp: i64 },
+    SignedAfterKeyExpiry { iat: i64, key_exp: i64 },
+    IssuerCountryMismatch { iss: String, country_code: String },
+}
+
+impl VerificationError {
+    pub fn check_type(&self) -> CheckType {
+        match self {
+            VerificationError::PayloadExpired { .. } => CheckType::PayloadExpiry,
+            VerificationError::SignedAfterKeyExpiry { .. } => CheckType::KeyExpiry,
+            VerificationError::IssuerCountryMismatch { .. } => CheckType::Issuer,
+        }
+    }
+
+    pub fn code(&self) -> CheckCode {
+        match self {
+            VerificationError::PayloadExpired { .. } => CheckCode::PayloadExpired,
+            VerificationError::SignedAfterKeyExpiry { .. } => CheckCode::SignedAfterKeyExpiry,
+            VerificationError::IssuerCountryMismatch { .. } => CheckCode::IssuerCountryMismatch,
+        }
+    }
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::PayloadExpired { exp } => {
+                write!(f, "Certificate payload expired at {}", exp)
+            }
+            VerificationError::SignedAfterKeyExpiry { iat, key_exp } => {
+                write!(
+                    f,
+                    "Certificate was issued at {}, after the signing key expired at {}",
+                    iat, key_exp
+                )
+            }
+            VerificationError::IssuerCountryMismatch { iss, country_code } => {
+                write!(
+                    f,
+                    "Issuer '{}' does not match the country '{}' of the signing key",
+                    iss, country_code
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use ciborium::value::Value;
+use log::debug;
+
+use crate::{cwt_claims::CwtClaims, types::eu_dcc::EuDigitalCovidCertificate};
+
+/// hcert claim (reference: https://github.com/ehn-dcc-development/hcert-spec)
+pub const HCERT_CLAIM: i128 = -260;
+/// key of the EU Digital COVID Certificate inside the hcert claim
+pub const EU_DCC_V1: i128 = 1;
+
+/// Returns the EU DCC carried in the hcert claim (-260 / 1) of a CWT payload; `None` if the
+/// payload is not an EU DCC (e.g. a WHO DDCC payload)
+pub fn get_eu_dcc_struct(payload: &[u8]) -> anyhow::Result<Option<EuDigitalCovidCertificate>> {
+    let value: Value = match ciborium::de::from_reader(payload) {
+        Ok(v) => v,
+        Err(e) => {
+            let message = format!("Unable to decode CWT payload, error was: {:?}", e);
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    };
+    let claims = match CwtClaims::untag(value) {
+        Value::Map(m) => m,
+        _ => return Ok(None),
+    };
+    let hcert = match CwtClaims::get_claim(&claims, HCERT_CLAIM) {
+        Some(Value::Map(hcert)) => hcert,
+        _ => return Ok(None),
+    };
+    let eu_dcc_value = match CwtClaims::get_claim(hcert, EU_DCC_V1) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    // re-encode the claim so that it can be deserialized into the typed struct
+    let mut eu_dcc_bytes = Vec::new();
+    if let Err(e) = ciborium::ser::into_writer(eu_dcc_value, &mut eu_dcc_bytes) {
+        let message = format!("Unable to encode EU DCC claim, error was: {:?}", e);
+        debug!("{}", message);
+        return Err(anyhow::anyhow!(message));
+    }
+    let eu_dcc: EuDigitalCovidCertificate = match ciborium::de::from_reader(eu_dcc_bytes.as_slice())
+    {
+        Ok(v) => v,
+        Err(e) => {
+            let message = format!("Invalid EU DCC payload, error was: {}", e);
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    };
+    let entries = [
+        eu_dcc.vaccinations.as_ref().map(|v| v.len()),
+        eu_dcc.tests.as_ref().map(|t| t.len()),
+        eu_dcc.recoveries.as_ref().map(|r| r.len()),
+    ]
+    .iter()
+    .flatten()
+    .sum::<usize>();
+    if entries == 0 {
+        let message = "EU DCC payload has no vaccination, test or recovery entry";
+        debug!("{}", message);
+        return Err(anyhow::anyhow!(message));
+    }
+    Ok(Some(eu_dcc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_owned())
+    }
+
+    fn encode(value: Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn hcert_payload(eu_dcc: Value) -> Vec<u8> {
+        encode(Value::Map(vec![
+            (Value::Integer(1.into()), text("AT")),
+            (
+                Value::Integer((-260).into()),
+                Value::Map(vec![(Value::Integer(1.into()), eu_dcc)]),
+            ),
+        ]))
+    }
+
+    fn name_and_birth_date() -> Vec<(Value, Value)> {
+        vec![
+            (text("ver"), text("1.3.0")),
+            (
+                text("nam"),
+                Value::Map(vec![
+                    (text("fn"), text("Musterfrau-Gößinger")),
+                    (text("fnt"), text("MUSTERFRAU<GOESSINGER")),
+                    (text("gn"), text("Gabriele")),
+                    (text("gnt"), text("GABRIELE")),
+                ]),
+            ),
+            (text("dob"), text("1998-02-26")),
+        ]
+    }
+
+    #[test]
+    fn get_eu_dcc_vaccination_test() {
+        let mut eu_dcc = name_and_birth_date();
+        eu_dcc.push((
+            text("v"),
+            Value::Array(vec![Value::Map(vec![
+                (text("tg"), text("840539006")),
+                (text("vp"), text("1119349007")),
+                (text("mp"), text("EU/1/20/1528")),
+                (text("ma"), text("ORG-100030215")),
+                (text("dn"), Value::Integer(1.into())),
+                (text("sd"), Value::Integer(2.into())),
+                (text("dt"), text("2021-02-18")),
+                (text("co"), text("AT")),
+                (text("is"), text("Ministry of Health, Austria")),
+                (
+                    text("ci"),
+                    text("URN:UVCI:01:AT:10807843F94AEE0EE5093FBC254BD813#B"),
+                ),
+            ])]),
+        ));
+        let eu_dcc = get_eu_dcc_struct(&hcert_payload(Value::Map(eu_dcc)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            eu_dcc.name.standardised_family_name,
+            "MUSTERFRAU<GOESSINGER"
+        );
+        let vaccinations = eu_dcc.vaccinations.unwrap();
+        assert_eq!(vaccinations.len(), 1);
+        assert_eq!(vaccinations[0].dose_number, 1);
+        assert_eq!(vaccinations[0].total_doses, 2);
+        assert_eq!(vaccinations[0].country, "AT");
+        assert!(eu_dcc.tests.is_none());
+        assert!(eu_dcc.recoveries.is_none());
+    }
+
+    #[test]
+    fn get_eu_dcc_recovery_test() {
+        let mut eu_dcc = name_and_birth_date();
+        eu_dcc.push((
+            text("r"),
+            Value::Array(vec![Value::Map(vec![
+                (text("tg"), text("840539006")),
+                (text("fr"), text("2021-02-20")),
+                (text("co"), text("AT")),
+                (text("is"), text("Ministry of Health, Austria")),
+                (text("df"), text("2021-04-04")),
+                (text("du"), text("2021-10-04")),
+                (
+                    text("ci"),
+                    text("URN:UVCI:01:AT:858CC18CFCF5965EF82F60E493349AA5#K"),
+                ),
+            ])]),
+        ));
+        let eu_dcc = get_eu_dcc_struct(&hcert_payload(Value::Map(eu_dcc)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(eu_dcc.recoveries.unwrap()[0].valid_until, "2021-10-04");
+    }
+
+    #[test]
+    fn get_eu_dcc_without_entries_test() {
+        let eu_dcc = Value::Map(name_and_birth_date());
+        assert!(get_eu_dcc_struct(&hcert_payload(eu_dcc)).is_err());
+    }
+
+    #[test]
+    fn get_eu_dcc_not_an_eu_dcc_test() {
+        let payload = encode(Value::Map(vec![
+            (Value::Integer(1.into()), text("CHL")),
+            (
+                Value::Integer((-260).into()),
+                Value::Map(vec![(Value::Integer((-6).into()), Value::Map(vec![]))]),
+            ),
+        ]));
+        assert!(get_eu_dcc_struct(&payload).unwrap().is_none());
+    }
+}
@@ -0,0 +1,1126 @@
+use std::io::Read;
+
+use base45::decode;
+use cose::{keys::CoseKey, message::CoseMessage};
+use flate2::read::ZlibDecoder;
+use log::debug;
+
+use crate::{
+    country_code::normalize_country_code,
+    cwt_claims::CwtClaims,
+    ddcc::get_ddcc_struct,
+    error::VerificationError,
+    eu_dcc::get_eu_dcc_struct,
+    key_provider::{KeyProvider, TrustedKey},
+    types::{
+        eu_dcc::EuDigitalCovidCertificate,
+        hc1::{
+            CheckCode, CheckStatus, CheckType, DdccCoreDataSet, Hc1Verification, PayloadType,
+            VerificationCheck,
+        },
+    },
+    x509::{DccCertificateType, X509Utils},
+};
+
+/// Returns cose keys according to cose-rust library format, each one paired with the trusted key it comes from.
+/// Keys that can't be converted to the cose-rust library format are omitted.
+pub fn to_cose_keys(
+    trusted_keys: Vec<TrustedKey>,
+    signing_alg: &i32,
+) -> Vec<(TrustedKey, CoseKey)> {
+    trusted_keys
+        .into_iter()
+        .filter_map(|trusted_key| {
+            match X509Utils::pem_to_cose_keys(vec![trusted_key.pem.clone()], signing_alg) {
+                Some(mut cose_keys) => cose_keys.pop().map(|cose_key| (trusted_key, cose_key)),
+                None => None,
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Tries the passed keys one by one against the message, returns the key that verified it
+fn find_verifying_key(
+    message: &mut CoseMessage,
+    cose_keys: Vec<(TrustedKey, CoseKey)>,
+) -> Option<TrustedKey> {
+    cose_keys
+        .into_iter()
+        .enumerate()
+        .find(|(idx, (trusted_key, key))| {
+            match message.key(&key) {
+                Ok(_) => {}
+                Err(e) => {
+                    debug!(
+                        "Key attachment failed for kid {}: {:?}",
+                        trusted_key.kid, &e
+                    );
+                    return false;
+                }
+            };
+            match message.decode(None, None) {
+                Ok(_) => {
+                    debug!(
+                        "Successful verification in iteration #{} with kid {}",
+                        idx + 1,
+                        trusted_key.kid
+                    );
+                    return true;
+                }
+                Err(e) => {
+                    debug!(
+                        "Validation failed in iteration #{} with kid {}: {:?}",
+                        idx + 1,
+                        trusted_key.kid,
+                        &e
+                    );
+                    return false;
+                }
+            }
+        })
+        .map(|(_, (trusted_key, _))| trusted_key)
+}
+
+/// Outcome of looking up trusted keys and verifying the message signature with them
+#[derive(Debug, Clone)]
+pub struct SignatureVerification {
+    /// kid (base64) found in the message headers
+    pub header_kid: Option<String>,
+    /// number of trusted keys whose kid matched the header kid
+    pub kid_matches: usize,
+    /// key that verified the message, `None` if no key did
+    pub trusted_key: Option<TrustedKey>,
+    /// compromised key, excluded from the trusted keys, that verified the message
+    pub compromised_key: Option<TrustedKey>,
+}
+
+fn get_keys_error(e: anyhow::Error) -> anyhow::Error {
+    let message = "Internal Error while getting keys";
+    debug!("{}, error was: {}", message, &e);
+    anyhow::anyhow!(message)
+}
+
+/// Verifies the message signature against the keys of the signer country.
+/// Keys are looked up by the `kid` found in the protected or unprotected header; loading every
+/// key of the country is only done as a fallback (e.g. when the header carries no `kid`).
+/// Compromised keys are only trusted for certificates issued (`issued_at`) before the compromise.
+pub async fn is_valid_message<P: KeyProvider + ?Sized>(
+    key_provider: &mut P,
+    message: &mut CoseMessage,
+    country_code: &str,
+    issued_at: Option<i64>,
+) -> anyhow::Result<SignatureVerification> {
+    let alg;
+    match message.header.alg {
+        Some(v) => {
+            alg = v;
+        }
+        None => {
+            let message = "No algoritm found for incoming message";
+            debug!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+    }
+    let header_kid = message
+        .header
+        .kid
+        .clone()
+        .map(|kid| X509Utils::encode_kid(&kid));
+    let mut kid_matches = 0;
+    match &header_kid {
+        Some(kid) => {
+            let kid_keys = key_provider
+                .get_keys_by_kid(country_code, kid)
+                .await
+                .map_err(get_keys_error)?
+                .into_iter()
+                .filter(|trusted_key| trusted_key.is_usable_at(issued_at))
+                .collect::<Vec<_>>();
+            kid_matches = kid_keys.len();
+            if let Some(found) = find_verifying_key(message, to_cose_keys(kid_keys, &alg)) {
+                return Ok(SignatureVerification {
+                    header_kid,
+                    kid_matches,
+                    trusted_key: Some(found),
+                    compromised_key: None,
+                });
+            }
+            debug!(
+                "No key matched kid {}, falling back to all keys for country {}",
+                kid, country_code
+            );
+        }
+        None => {
+            debug!(
+                "No kid found in message headers, trying all keys for country {}",
+                country_code
+            );
+        }
+    }
+    let keys = key_provider
+        .get_keys(country_code)
+        .await
+        .map_err(get_keys_error)?;
+    let (usable_keys, unusable_keys): (Vec<_>, Vec<_>) = keys
+        .into_iter()
+        .partition(|trusted_key| trusted_key.is_usable_at(issued_at));
+    // keys rejected by the CSCA trust store are not trusted at all, not even to tell apart a
+    // compromised signer
+    let compromised_keys = unusable_keys
+        .into_iter()
+        .filter(|trusted_key| trusted_key.is_chain_trusted != Some(false))
+        .collect::<Vec<_>>();
+    // keys matching the header kid were already tried
+    let other_keys = usable_keys
+        .into_iter()
+        .filter(|trusted_key| Some(&trusted_key.kid) != header_kid.as_ref())
+        .collect::<Vec<_>>();
+    let trusted_key = find_verifying_key(message, to_cose_keys(other_keys, &alg));
+    if trusted_key.is_some() {
+        return Ok(SignatureVerification {
+            header_kid,
+            kid_matches,
+            trusted_key,
+            compromised_key: None,
+        });
+    }
+    debug!("No key matched");
+    // tells apart a signature made with a revoked (compromised) key from an unknown one
+    let compromised_key = find_verifying_key(message, to_cose_keys(compromised_keys, &alg));
+    if let Some(compromised_key) = &compromised_key {
+        debug!(
+            "Message signed with compromised key {}",
+            compromised_key.kid
+        );
+    }
+    Ok(SignatureVerification {
+        header_kid,
+        kid_matches,
+        trusted_key: None,
+        compromised_key,
+    })
+}
+
+/// Builds the key trust, signature and compromised key checks out of the signature verification.
+/// A compromised key is either accepted because the certificate predates the compromise or
+/// excluded from the trusted keys; the compromised key check tells which one applied.
+pub fn get_signature_checks(
+    verification: &SignatureVerification,
+    signer_country_code: &str,
+) -> Vec<VerificationCheck> {
+    let country = Some(signer_country_code.to_owned());
+    match (&verification.trusted_key, &verification.compromised_key) {
+        (Some(trusted_key), _) => {
+            let kid = Some(trusted_key.kid.clone());
+            let compromised_check = if trusted_key.is_compromised {
+                VerificationCheck {
+                    code: CheckCode::KeyCompromisedAfterIssuance,
+                    message: Some(format!(
+                        "Signing key is flagged as compromised since {:?}, after the certificate was issued",
+                        trusted_key.compromised_at
+                    )),
+                    ..VerificationCheck::passed(CheckType::CompromisedKey)
+                }
+            } else {
+                VerificationCheck::passed(CheckType::CompromisedKey)
+            };
+            vec![
+                VerificationCheck::passed(CheckType::KeyTrust)
+                    .with_kid(kid.clone())
+                    .with_country(country),
+                VerificationCheck::passed(CheckType::Signature).with_kid(kid.clone()),
+                compromised_check.with_kid(kid),
+            ]
+        }
+        (None, Some(compromised_key)) => {
+            let kid = Some(compromised_key.kid.clone());
+            let message = match compromised_key.compromised_at {
+                Some(compromised_at) => format!(
+                    "Signing key is flagged as compromised since {}, before the certificate was issued",
+                    compromised_at
+                ),
+                None => "Signing key is flagged as compromised".to_owned(),
+            };
+            vec![
+                VerificationCheck::failed(CheckType::KeyTrust, CheckCode::KeyCompromised, &message)
+                    .with_kid(kid.clone())
+                    .with_country(country),
+                VerificationCheck::failed(
+                    CheckType::Signature,
+                    CheckCode::KeyCompromised,
+                    "Signature was made with a key excluded from the trusted keys",
+                )
+                .with_kid(kid.clone()),
+                VerificationCheck::failed(
+                    CheckType::CompromisedKey,
+                    CheckCode::KeyCompromised,
+                    &message,
+                )
+                .with_kid(kid),
+            ]
+        }
+        (None, None) => {
+            let kid = verification.header_kid.clone();
+            let key_trust_check = if verification.kid_matches > 0 {
+                VerificationCheck::passed(CheckType::KeyTrust)
+            } else {
+                VerificationCheck::failed(
+                    CheckType::KeyTrust,
+                    CheckCode::KeyNotFound,
+                    "No trusted key found for the signer country",
+                )
+            };
+            vec![
+                key_trust_check.with_kid(kid.clone()).with_country(country),
+                VerificationCheck::failed(
+                    CheckType::Signature,
+                    CheckCode::InvalidSignature,
+                    "No trusted key verified the signature",
+                )
+                .with_kid(kid),
+                VerificationCheck::skipped(CheckType::CompromisedKey, "No signing key identified"),
+            ]
+        }
+    }
+}
+
+/// Checks that the signing key extended key usage allows every certificate type found in the payload
+pub fn get_key_usage_check(
+    trusted_key: Option<&TrustedKey>,
+    decoded_payload: &DecodedPayload,
+) -> VerificationCheck {
+    let trusted_key = match trusted_key {
+        Some(v) => v,
+        None => {
+            return VerificationCheck::skipped(CheckType::KeyUsage, "No signing key identified")
+        }
+    };
+    let kid = Some(trusted_key.kid.clone());
+    let allowed_types = match &trusted_key.certificate_types {
+        Some(v) => v,
+        None => return VerificationCheck::passed(CheckType::KeyUsage).with_kid(kid),
+    };
+    let payload_types = decoded_payload.get_certificate_types();
+    if payload_types.is_empty() {
+        return VerificationCheck::skipped(
+            CheckType::KeyUsage,
+            "No certificate type found in payload",
+        )
+        .with_kid(kid);
+    }
+    match payload_types
+        .iter()
+        .find(|payload_type| !allowed_types.contains(payload_type))
+    {
+        Some(not_allowed) => VerificationCheck::failed(
+            CheckType::KeyUsage,
+            CheckCode::CertificateTypeNotAllowed,
+            &format!(
+                "Signing key may only sign {} certificates, got a {} certificate",
+                allowed_types
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                not_allowed
+            ),
+        )
+        .with_kid(kid),
+        None => VerificationCheck::passed(CheckType::KeyUsage).with_kid(kid),
+    }
+}
+
+/// Checks the CWT claims against the current time and the key that verified the signature:
+/// its expiration and the country it was registered for. Returns every check that failed.
+pub fn check_cwt_claims(
+    claims: &CwtClaims,
+    key_exp: Option<i64>,
+    key_country_code: Option<&str>,
+    now: i64,
+) -> Vec<VerificationError> {
+    let mut errors = Vec::new();
+    if let Some(exp) = claims.exp {
+        if exp < now {
+            errors.push(VerificationError::PayloadExpired { exp });
+        }
+    }
+    if let (Some(iat), Some(key_exp)) = (claims.iat, key_exp) {
+        if iat > key_exp {
+            errors.push(VerificationError::SignedAfterKeyExpiry { iat, key_exp });
+        }
+    }
+    if let (Some(iss), Some(key_country_code)) = (&claims.iss, key_country_code) {
+        if normalize_country_code(iss) != normalize_country_code(key_country_code) {
+            errors.push(VerificationError::IssuerCountryMismatch {
+                iss: iss.to_owned(),
+                country_code: key_country_code.to_owned(),
+            });
+        }
+    }
+    errors
+}
+
+/// Builds the key expiry, payload expiry and issuer checks out of the CWT claims and the key
+/// that verified the signature
+pub fn get_claim_checks(
+    claims: &CwtClaims,
+    trusted_key: Option<&TrustedKey>,
+    now: i64,
+) -> Vec<VerificationCheck> {
+    let key_exp = trusted_key.and_then(|trusted_key| trusted_key.get_expiration());
+    let kid = trusted_key.map(|trusted_key| trusted_key.kid.clone());
+    let key_country_code = trusted_key.map(|trusted_key| trusted_key.country_code.clone());
+    let errors = check_cwt_claims(claims, key_exp, key_country_code.as_deref(), now);
+    vec![
+        (
+            CheckType::KeyExpiry,
+            claims.iat.is_some() && key_exp.is_some(),
+            "Missing 'iat' claim or signing key expiration",
+        ),
+        (
+            CheckType::PayloadExpiry,
+            claims.exp.is_some(),
+            "Missing 'exp' claim",
+        ),
+        (
+            CheckType::Issuer,
+            claims.iss.is_some() && key_country_code.is_some(),
+            "Missing 'iss' claim or signing key",
+        ),
+    ]
+    .into_iter()
+    .map(|(check_type, evaluated, skip_message)| {
+        let check = match errors.iter().find(|e| e.check_type() == check_type) {
+            Some(e) => VerificationCheck::failed(check_type, e.code(), &e.to_string()),
+            None if evaluated => VerificationCheck::passed(check_type),
+            None => VerificationCheck::skipped(check_type, skip_message),
+        };
+        match check_type {
+            CheckType::KeyExpiry => check.with_kid(kid.clone()),
+            CheckType::Issuer => check.with_country(key_country_code.clone()),
+            _ => check,
+        }
+    })
+    .collect::<Vec<_>>()
+}
+
+/// Returns the decoded (Base45 + zlib) COSE message bytes; on failure returns the check describing why
+pub fn decode_hc1(data: &str) -> Result<Vec<u8>, VerificationCheck> {
+    let data = data.trim();
+    let data: String = data.replace("HC1:", "");
+    match decode(&data) {
+        Ok(zlib_encoded) => {
+            let mut zlib_data = ZlibDecoder::new(zlib_encoded.as_slice());
+            let mut cose_full_message = Vec::new();
+            match zlib_data.read_to_end(&mut cose_full_message) {
+                Ok(_) => Ok(cose_full_message),
+                Err(e) => Err(VerificationCheck::failed(
+                    CheckType::Decoding,
+                    CheckCode::InvalidCompression,
+                    &format!("Invalid zlib compressed message: {}", e),
+                )),
+            }
+        }
+        Err(e) => Err(VerificationCheck::failed(
+            CheckType::Decoding,
+            CheckCode::InvalidBase45,
+            &format!("Invalid Base45 encoded message: {}", e),
+        )),
+    }
+}
+
+/// Certificate content found in the CWT payload, either a WHO DDCC or an EU DCC
+#[derive(Debug, Clone, Default)]
+pub struct DecodedPayload {
+    pub payload_type: Option<PayloadType>,
+    pub ddcc_core_data_set: Option<DdccCoreDataSet>,
+    pub eu_dcc: Option<EuDigitalCovidCertificate>,
+}
+
+impl DecodedPayload {
+    /// Certificate types (vaccination, test, recovery) carried by the payload
+    pub fn get_certificate_types(&self) -> Vec<DccCertificateType> {
+        let present = match (&self.ddcc_core_data_set, &self.eu_dcc) {
+            (Some(ddcc), _) => [
+                ddcc.test_result.is_some(),
+                ddcc.vaccination.is_some(),
+                ddcc.recovery.is_some(),
+            ],
+            (None, Some(eu_dcc)) => [
+                eu_dcc.tests.as_ref().map_or(false, |v| !v.is_empty()),
+                eu_dcc
+                    .vaccinations
+                    .as_ref()
+                    .map_or(false, |v| !v.is_empty()),
+                eu_dcc.recoveries.as_ref().map_or(false, |v| !v.is_empty()),
+            ],
+            (None, None) => [false; 3],
+        };
+        [
+            DccCertificateType::Test,
+            DccCertificateType::Vaccination,
+            DccCertificateType::Recovery,
+        ]
+        .into_iter()
+        .zip(present)
+        .filter_map(|(certificate_type, present)| present.then_some(certificate_type))
+        .collect()
+    }
+}
+
+/// Detects the payload type from the hcert claim and parses it, pushing the schema check outcome
+/// into `checks`
+pub fn decode_payload(payload: &Vec<u8>, checks: &mut Vec<VerificationCheck>) -> DecodedPayload {
+    let invalid_schema =
+        |checks: &mut Vec<VerificationCheck>, payload_type: Option<PayloadType>, message: &str| {
+            checks.push(VerificationCheck::failed(
+                CheckType::Schema,
+                CheckCode::InvalidSchema,
+                message,
+            ));
+            DecodedPayload {
+                payload_type,
+                ..Default::default()
+            }
+        };
+    match get_eu_dcc_struct(payload) {
+        Ok(Some(eu_dcc)) => {
+            checks.push(VerificationCheck::passed(CheckType::Schema));
+            DecodedPayload {
+                payload_type: Some(PayloadType::EuDcc),
+                ddcc_core_data_set: None,
+                eu_dcc: Some(eu_dcc),
+            }
+        }
+        Ok(None) => match get_ddcc_struct(payload) {
+            Ok(Some(ddcc_core_data_set)) => {
+                checks.push(VerificationCheck::passed(CheckType::Schema));
+                DecodedPayload {
+                    payload_type: Some(PayloadType::Ddcc),
+                    ddcc_core_data_set: Some(ddcc_core_data_set),
+                    eu_dcc: None,
+                }
+            }
+            Ok(None) => invalid_schema(
+                checks,
+                None,
+                "No EU DCC (-260 / 1) or DDCC (-260 / -6) found in the hcert claim",
+            ),
+            Err(e) => invalid_schema(checks, Some(PayloadType::Ddcc), &e.to_string()),
+        },
+        Err(e) => invalid_schema(checks, Some(PayloadType::EuDcc), &e.to_string()),
+    }
+}
+
+/// The signer country is the `iss` claim, alpha2 (EU DCC) or alpha3 (DDCC)
+pub fn get_payload_signer_country_code(cwt_claims: &CwtClaims) -> Option<String> {
+    cwt_claims
+        .iss
+        .as_ref()
+        .and_then(|iss| normalize_country_code(iss))
+}
+
+fn invalid_result(
+    checks: Vec<VerificationCheck>,
+    decoded_payload: DecodedPayload,
+) -> Hc1Verification {
+    Hc1Verification {
+        is_valid: false,
+        kid: None,
+        checks,
+        payload_type: decoded_payload.payload_type,
+        ddcc_core_data_set: decoded_payload.ddcc_core_data_set,
+        eu_dcc: decoded_payload.eu_dcc,
+    }
+}
+
+/// Runs every check on a base45 HC1 certificate, with `now` (unix seconds) as the time expiry
+/// is evaluated at. Fails only on internal errors (e.g. the keys could not be fetched); an
+/// invalid certificate is reported through its checks
+pub async fn verify_hc1<P: KeyProvider + ?Sized>(
+    key_provider: &mut P,
+    data: &str,
+    now: i64,
+) -> anyhow::Result<Hc1Verification> {
+    let mut checks = Vec::new();
+    let cose_full_message = match decode_hc1(data) {
+        Ok(v) => v,
+        Err(check) => {
+            checks.push(check);
+            return Ok(invalid_result(checks, DecodedPayload::default()));
+        }
+    };
+
+    let mut cose_message = CoseMessage::new_sign();
+    cose_message.bytes = cose_full_message;
+    if let Err(e) = cose_message.init_decoder(None) {
+        debug!("Unable to init the COSE decoder: {:?}", &e);
+        checks.push(VerificationCheck::failed(
+            CheckType::Decoding,
+            CheckCode::InvalidCose,
+            "Failed while trying to decode COSE message",
+        ));
+        return Ok(invalid_result(checks, DecodedPayload::default()));
+    }
+    let payload = cose_message.payload.clone();
+    let cwt_claims = match CwtClaims::from_payload(&payload) {
+        Ok(claims) => claims,
+        Err(e) => {
+            checks.push(VerificationCheck::failed(
+                CheckType::Decoding,
+                CheckCode::InvalidCwt,
+                &e.to_string(),
+            ));
+            return Ok(invalid_result(checks, DecodedPayload::default()));
+        }
+    };
+    checks.push(VerificationCheck::passed(CheckType::Decoding));
+
+    let decoded_payload = decode_payload(&payload, &mut checks);
+
+    let signer_country_code = match get_payload_signer_country_code(&cwt_claims) {
+        Some(v) => v,
+        None => {
+            checks.push(VerificationCheck::failed(
+                CheckType::KeyTrust,
+                CheckCode::SignerCountryNotFound,
+                "signer country code not found",
+            ));
+            return Ok(invalid_result(checks, decoded_payload));
+        }
+    };
+
+    let verification = is_valid_message(
+        key_provider,
+        &mut cose_message,
+        &signer_country_code,
+        cwt_claims.iat,
+    )
+    .await?;
+    checks.append(&mut get_signature_checks(
+        &verification,
+        &signer_country_code,
+    ));
+    checks.push(get_key_usage_check(
+        verification.trusted_key.as_ref(),
+        &decoded_payload,
+    ));
+    checks.append(&mut get_claim_checks(
+        &cwt_claims,
+        verification.trusted_key.as_ref(),
+        now,
+    ));
+
+    let is_valid = checks
+        .iter()
+        .all(|check| check.status != CheckStatus::Failed);
+    Ok(Hc1Verification {
+        is_valid,
+        kid: verification.trusted_key.map(|trusted_key| trusted_key.kid),
+        checks,
+        payload_type: decoded_payload.payload_type,
+        ddcc_core_data_set: decoded_payload.ddcc_core_data_set,
+        eu_dcc: decoded_payload.eu_dcc,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ciborium::value::Value as CborValue;
+    use cose::algs;
+    use openssl::nid::Nid;
+
+    use crate::{
+        key_provider::InMemoryKeyProvider,
+        signer::{get_ddcc_cwt_payload, Hc1Signer},
+        test_support::{ec_key, get_p256_pem_test_keys, get_rsa_pem_test_keys, self_signed_pem},
+    };
+
+    use super::*;
+    // use std::println;
+    #[test]
+    fn get_cose_keys_containing_rsa_key_test() {
+        let pem_keys = get_rsa_pem_test_keys().unwrap();
+        let cose_keys = X509Utils::pem_to_cose_keys(pem_keys, &algs::PS256).unwrap();
+        assert_eq!(cose_keys.len(), 2)
+    }
+
+    #[test]
+    fn get_cose_keys_containing_p256_key_test() {
+        let pem_keys = get_p256_pem_test_keys().unwrap();
+        let cose_keys = X509Utils::pem_to_cose_keys(pem_keys, &algs::ES256).unwrap();
+        assert_eq!(cose_keys.len(), 1);
+    }
+
+    #[test]
+    fn check_cwt_claims_test() {
+        let claims = CwtClaims {
+            iss: Some("CL".to_owned()),
+            iat: Some(1_700_000_000),
+            exp: Some(1_800_000_000),
+        };
+        let errors = check_cwt_claims(&claims, Some(1_750_000_000), Some("CHL"), 1_710_000_000);
+        assert!(errors.is_empty());
+
+        let errors = check_cwt_claims(&claims, Some(1_650_000_000), Some("ARG"), 1_810_000_000);
+        assert_eq!(
+            errors,
+            vec![
+                VerificationError::PayloadExpired { exp: 1_800_000_000 },
+                VerificationError::SignedAfterKeyExpiry {
+                    iat: 1_700_000_000,
+                    key_exp: 1_650_000_000
+                },
+                VerificationError::IssuerCountryMismatch {
+                    iss: "CL".to_owned(),
+                    country_code: "ARG".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn is_usable_at_test() {
+        let trusted_key = TrustedKey {
+            kid: "CWKd9M24nDY=".to_owned(),
+            country_code: "CHL".to_owned(),
+            pem: get_rsa_pem_test_keys().unwrap().remove(0),
+            exp: None,
+            is_compromised: false,
+            compromised_at: None,
+            certificate_types: None,
+            is_chain_trusted: None,
+        };
+        assert!(trusted_key.is_usable_at(None));
+        let compromised_key = TrustedKey {
+            is_compromised: true,
+            compromised_at: Some(1_700_000_000),
+            ..trusted_key
+        };
+        assert!(compromised_key.is_usable_at(Some(1_690_000_000)));
+        assert!(!compromised_key.is_usable_at(Some(1_710_000_000)));
+        assert!(!compromised_key.is_usable_at(None));
+        let untrusted_chain_key = TrustedKey {
+            is_chain_trusted: Some(false),
+            ..trusted_key.clone()
+        };
+        assert!(!untrusted_chain_key.is_usable_at(Some(1_690_000_000)));
+        let trusted_chain_key = TrustedKey {
+            is_chain_trusted: Some(true),
+            ..trusted_key
+        };
+        assert!(trusted_chain_key.is_usable_at(None));
+    }
+
+    #[test]
+    fn get_key_usage_check_test() {
+        let test_only_key = TrustedKey {
+            kid: "CWKd9M24nDY=".to_owned(),
+            country_code: "CHL".to_owned(),
+            pem: get_rsa_pem_test_keys().unwrap().remove(0),
+            exp: None,
+            is_compromised: false,
+            compromised_at: None,
+            certificate_types: Some(vec![DccCertificateType::Test]),
+            is_chain_trusted: None,
+        };
+        let eu_dcc: EuDigitalCovidCertificate = serde_json::from_value(serde_json::json!({
+            "version": "1.3.0",
+            "name": { "standardisedFamilyName": "MUSTERFRAU" },
+            "dateOfBirth": "1998-02-26",
+            "vaccinations": [{
+                "targetDisease": "840539006",
+                "vaccine": "1119349007",
+                "medicinalProduct": "EU/1/20/1528",
+                "manufacturer": "ORG-100030215",
+                "doseNumber": 1,
+                "totalDoses": 2,
+                "date": "2021-02-18",
+                "country": "AT",
+                "issuer": "Ministry of Health, Austria",
+                "certificateIdentifier": "URN:UVCI:01:AT:10807843F94AEE0EE5093FBC254BD813#B"
+            }]
+        }))
+        .unwrap();
+        let decoded_payload = DecodedPayload {
+            payload_type: Some(PayloadType::EuDcc),
+            ddcc_core_data_set: None,
+            eu_dcc: Some(eu_dcc),
+        };
+        assert_eq!(
+            decoded_payload.get_certificate_types(),
+            vec![DccCertificateType::Vaccination]
+        );
+
+        let check = get_key_usage_check(Some(&test_only_key), &decoded_payload);
+        assert_eq!(check.status, CheckStatus::Failed);
+        assert_eq!(check.code, CheckCode::CertificateTypeNotAllowed);
+
+        let unrestricted_key = TrustedKey {
+            certificate_types: None,
+            ..test_only_key.clone()
+        };
+        let check = get_key_usage_check(Some(&unrestricted_key), &decoded_payload);
+        assert_eq!(check.status, CheckStatus::Passed);
+
+        let vaccination_key = TrustedKey {
+            certificate_types: Some(vec![
+                DccCertificateType::Vaccination,
+                DccCertificateType::Recovery,
+            ]),
+            ..test_only_key
+        };
+        let check = get_key_usage_check(Some(&vaccination_key), &decoded_payload);
+        assert_eq!(check.status, CheckStatus::Passed);
+
+        let check = get_key_usage_check(None, &decoded_payload);
+        assert_eq!(check.status, CheckStatus::Skipped);
+    }
+
+    #[test]
+    fn get_claim_checks_without_claims_test() {
+        let checks = get_claim_checks(&CwtClaims::default(), None, 1_710_000_000);
+        assert_eq!(checks.len(), 3);
+        assert!(checks
+            .iter()
+            .all(|check| check.status == CheckStatus::Skipped));
+    }
+
+    /// CWT payload with the DDCC under the hcert claim (-260 / -6)
+    fn encode_ddcc(entries: Vec<(&str, CborValue)>) -> Vec<u8> {
+        let map = entries
+            .into_iter()
+            .map(|(k, v)| (CborValue::Text(k.to_owned()), v))
+            .collect::<Vec<_>>();
+        let claims = CborValue::Map(vec![
+            (
+                CborValue::Integer(1.into()),
+                CborValue::Text("CHL".to_owned()),
+            ),
+            (
+                CborValue::Integer((-260).into()),
+                CborValue::Map(vec![(CborValue::Integer((-6).into()), CborValue::Map(map))]),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&claims, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn code(code: &str) -> CborValue {
+        CborValue::Map(vec![(
+            CborValue::Text("code".to_owned()),
+            CborValue::Text(code.to_owned()),
+        )])
+    }
+
+    #[test]
+    fn decode_payload_with_test_result_test() {
+        let test = CborValue::Map(vec![
+            (CborValue::Text("pathogen".to_owned()), code("840539006")),
+            (CborValue::Text("type".to_owned()), code("LP6464-4")),
+            (CborValue::Text("result".to_owned()), code("260415000")),
+            (
+                CborValue::Text("date".to_owned()),
+                CborValue::Text("2023-10-01T10:00:00Z".to_owned()),
+            ),
+        ]);
+        let payload = encode_ddcc(vec![
+            ("name", CborValue::Text("Jane Doe".to_owned())),
+            ("test", test),
+        ]);
+        let mut checks = vec![];
+        let decoded_payload = decode_payload(&payload, &mut checks);
+        assert_eq!(checks[0].status, CheckStatus::Passed);
+        assert_eq!(decoded_payload.payload_type, Some(PayloadType::Ddcc));
+        let ddcc_core_data_set = decoded_payload.ddcc_core_data_set.unwrap();
+        assert!(ddcc_core_data_set.vaccination.is_none());
+        assert!(ddcc_core_data_set.recovery.is_none());
+        let test_result = ddcc_core_data_set.test_result.unwrap();
+        assert_eq!(test_result.result.code, Some("260415000".to_owned()));
+        assert!(test_result.brand.is_none());
+    }
+
+    #[test]
+    fn decode_payload_without_events_test() {
+        let payload = encode_ddcc(vec![("name", CborValue::Text("Jane Doe".to_owned()))]);
+        let mut checks = vec![];
+        let decoded_payload = decode_payload(&payload, &mut checks);
+        assert_eq!(checks[0].status, CheckStatus::Failed);
+        assert!(decoded_payload.ddcc_core_data_set.is_none());
+    }
+
+    #[test]
+    fn decode_payload_missing_test_result_test() {
+        let test = CborValue::Map(vec![
+            (CborValue::Text("pathogen".to_owned()), code("840539006")),
+            (CborValue::Text("type".to_owned()), code("LP6464-4")),
+            (
+                CborValue::Text("date".to_owned()),
+                CborValue::Text("2023-10-01T10:00:00Z".to_owned()),
+            ),
+        ]);
+        let payload = encode_ddcc(vec![("test", test)]);
+        let mut checks = vec![];
+        decode_payload(&payload, &mut checks);
+        assert_eq!(checks[0].status, CheckStatus::Failed);
+        assert!(checks[0]
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("'test.result' is missing"));
+    }
+
+    #[test]
+    fn get_payload_signer_country_code_test() {
+        let payload = encode_ddcc(vec![]);
+        let cwt_claims = CwtClaims::from_payload(&payload).unwrap();
+        assert_eq!(
+            get_payload_signer_country_code(&cwt_claims),
+            Some("CHL".to_owned())
+        );
+        let cwt_claims = CwtClaims {
+            iss: Some("CL".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_payload_signer_country_code(&cwt_claims),
+            Some("CHL".to_owned())
+        );
+        assert_eq!(get_payload_signer_country_code(&CwtClaims::default()), None);
+    }
+
+    /// ES256 signed HC1 certificate holding a DDCC test result, along with its DSC
+    fn sign_hc1(iat: i64, exp: i64) -> (String, String) {
+        let private_key = ec_key(Nid::X9_62_PRIME256V1);
+        let pem = self_signed_pem(&private_key);
+
+        let test = CborValue::Map(vec![
+            (CborValue::Text("pathogen".to_owned()), code("840539006")),
+            (CborValue::Text("type".to_owned()), code("LP6464-4")),
+            (CborValue::Text("result".to_owned()), code("260415000")),
+            (
+                CborValue::Text("date".to_owned()),
+                CborValue::Text("2023-10-01T10:00:00Z".to_owned()),
+            ),
+        ]);
+        let ddcc = CborValue::Map(vec![
+            (
+                CborValue::Text("name".to_owned()),
+                CborValue::Text("Jane Doe".to_owned()),
+            ),
+            (CborValue::Text("test".to_owned()), test),
+        ]);
+        let payload = get_ddcc_cwt_payload("CHL", iat, exp, ddcc).unwrap();
+        let data = Hc1Signer::new(pem.clone(), private_key)
+            .unwrap()
+            .sign(payload)
+            .unwrap();
+        (data, pem)
+    }
+
+    fn trusted_key(pem: String) -> TrustedKey {
+        TrustedKey {
+            kid: X509Utils::get_kid_string_from_pem(pem.clone()).unwrap(),
+            country_code: "CHL".to_owned(),
+            pem,
+            exp: None,
+            is_compromised: false,
+            compromised_at: None,
+            certificate_types: None,
+            is_chain_trusted: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_hc1_test() {
+        let now = 1_710_000_000;
+        let (data, pem) = sign_hc1(now - 3600, now + 3600);
+        let kid = X509Utils::get_kid_string_from_pem(pem.clone()).unwrap();
+        let mut key_provider = InMemoryKeyProvider::new(vec![trusted_key(pem)]);
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(verification.is_valid, "{:?}", verification.checks);
+        assert_eq!(verification.kid, Some(kid));
+        assert_eq!(verification.payload_type, Some(PayloadType::Ddcc));
+        assert_eq!(verification.ddcc_core_data_set.unwrap().name, "Jane Doe");
+
+        let verification = verify_hc1(&mut key_provider, &data, now + 7200)
+            .await
+            .unwrap();
+        assert!(!verification.is_valid);
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::PayloadExpiry)
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn verify_hc1_untrusted_chain_test() {
+        let now = 1_710_000_000;
+        let (data, pem) = sign_hc1(now - 3600, now + 3600);
+        let mut key_provider = InMemoryKeyProvider::new(vec![TrustedKey {
+            is_chain_trusted: Some(false),
+            ..trusted_key(pem)
+        }]);
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(!verification.is_valid);
+        assert!(verification.kid.is_none());
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::Signature)
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Failed);
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::KeyTrust)
+            .unwrap();
+        assert_eq!(check.code, CheckCode::KeyNotFound);
+    }
+
+    #[tokio::test]
+    async fn verify_hc1_unknown_key_test() {
+        let now = 1_710_000_000;
+        let (data, _) = sign_hc1(now - 3600, now + 3600);
+        let (_, other_pem) = sign_hc1(now - 3600, now + 3600);
+        let mut key_provider = InMemoryKeyProvider::new(vec![trusted_key(other_pem)]);
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(!verification.is_valid);
+        assert!(verification.kid.is_none());
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::KeyTrust)
+            .unwrap();
+        assert_eq!(check.code, CheckCode::KeyNotFound);
+
+        let verification = verify_hc1(&mut key_provider, "HC1:not base45", now)
+            .await
+            .unwrap();
+        assert!(!verification.is_valid);
+        assert_eq!(verification.checks[0].code, CheckCode::InvalidBase45);
+    }
+
+    /// Serves every key whatever the country asked for, like a trust list not split by country
+    struct AnyCountryKeyProvider {
+        trusted_keys: Vec<TrustedKey>,
+    }
+
+    #[async_trait::async_trait]
+    impl KeyProvider for AnyCountryKeyProvider {
+        async fn get_keys(&mut self, _country_code: &str) -> anyhow::Result<Vec<TrustedKey>> {
+            Ok(self.trusted_keys.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_hc1_issuer_country_mismatch_test() {
+        let now = 1_710_000_000;
+        // the payload names CHL as issuer, the key is registered for ARG
+        let (data, pem) = sign_hc1(now - 3600, now + 3600);
+        let arg_key = TrustedKey {
+            country_code: "ARG".to_owned(),
+            ..trusted_key(pem)
+        };
+        let mut key_provider = AnyCountryKeyProvider {
+            trusted_keys: vec![arg_key.clone()],
+        };
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(!verification.is_valid);
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::Signature)
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Passed);
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::Issuer)
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Failed);
+        assert_eq!(check.code, CheckCode::IssuerCountryMismatch);
+        assert_eq!(check.country, Some("ARG".to_owned()));
+
+        let mut key_provider = InMemoryKeyProvider::new(vec![arg_key]);
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(!verification.is_valid);
+        let check = verification
+            .checks
+            .iter()
+            .find(|check| check.check == CheckType::KeyTrust)
+            .unwrap();
+        assert_eq!(check.code, CheckCode::KeyNotFound);
+    }
+
+    /// Vectors signed outside this crate, so they do not share its encoding choices
+    #[tokio::test]
+    async fn verify_hc1_test_vectors_test() {
+        let test_vectors: serde_json::Value =
+            serde_json::from_str(include_str!("../test_vectors/hc1.json")).unwrap();
+        let now = test_vectors["iat"].as_i64().unwrap() + 3600;
+        for (alg, test_vector) in [("ES384", 0), ("ES512", 1), ("EdDSA", 2)] {
+            let test_vector = &test_vectors["vectors"][test_vector];
+            assert_eq!(test_vector["alg"], alg);
+            let pem = test_vector["pem"].as_str().unwrap().to_owned();
+            let data = test_vector["hc1"].as_str().unwrap();
+            let kid = X509Utils::get_kid_string_from_pem(pem.clone()).unwrap();
+            let mut key_provider = InMemoryKeyProvider::new(vec![trusted_key(pem)]);
+            let verification = verify_hc1(&mut key_provider, data, now).await.unwrap();
+            assert!(verification.is_valid, "{}: {:?}", alg, verification.checks);
+            assert_eq!(verification.kid, Some(kid));
+            assert_eq!(verification.ddcc_core_data_set.unwrap().name, "Jane Doe");
+        }
+    }
+
+    /// Counts the whole country loads to tell the kid lookup apart from the fallback
+    struct CountingKeyProvider {
+        key_provider: InMemoryKeyProvider,
+        country_loads: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl KeyProvider for CountingKeyProvider {
+        async fn get_keys(&mut self, country_code: &str) -> anyhow::Result<Vec<TrustedKey>> {
+            self.country_loads += 1;
+            self.key_provider.get_keys(country_code).await
+        }
+
+        async fn get_keys_by_kid(
+            &mut self,
+            country_code: &str,
+            kid: &str,
+        ) -> anyhow::Result<Vec<TrustedKey>> {
+            let keys = self.key_provider.get_keys(country_code).await?;
+            Ok(keys.into_iter().filter(|k| k.kid == kid).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn is_valid_message_kid_lookup_test() {
+        let now = 1_710_000_000;
+        let (data, pem) = sign_hc1(now - 3600, now + 3600);
+        let mut key_provider = CountingKeyProvider {
+            key_provider: InMemoryKeyProvider::new(vec![trusted_key(pem.clone())]),
+            country_loads: 0,
+        };
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(verification.is_valid, "{:?}", verification.checks);
+        assert_eq!(key_provider.country_loads, 0);
+
+        // the key is registered under another kid: found by the fallback over the whole country
+        let mut key_provider = CountingKeyProvider {
+            key_provider: InMemoryKeyProvider::new(vec![TrustedKey {
+                kid: "AAAAAAAAAAA=".to_owned(),
+                ..trusted_key(pem)
+            }]),
+            country_loads: 0,
+        };
+        let verification = verify_hc1(&mut key_provider, &data, now).await.unwrap();
+        assert!(verification.is_valid, "{:?}", verification.checks);
+        assert_eq!(key_provider.country_loads, 1);
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::x509::{DccCertificateType, X509Utils};
+
+/// A key from the trust list, as used for verification
+#[derive(Debug, Clone)]
+pub struct TrustedKey {
+    pub kid: String,
+    /// iso alpha3 country code the key was registered for
+    pub country_code: String,
+    pub pem: String,
+    /// validity set by the source of the key (e.g. `validTo` in the did registry)
+    pub exp: Option<i64>,
+    pub is_compromised: bool,
+    /// time since when the key is considered compromised
+    pub compromised_at: Option<i64>,
+    /// certificate types the key may sign (DSC extended key usage); `None` means any
+    pub certificate_types: Option<Vec<DccCertificateType>>,
+    /// whether the key certificate chains to a trusted CSCA of its country; `None` when it was
+    /// not evaluated (no CSCA configured)
+    pub is_chain_trusted: Option<bool>,
+}
+
+impl TrustedKey {
+    /// Returns the earliest among the key validity and the certificate (DSC) `notAfter`
+    pub fn get_expiration(&self) -> Option<i64> {
+        let certificate_exp = X509Utils::get_expiration_from_pem(self.pem.clone())
+            .ok()
+            .map(|v| v as i64);
+        match (self.exp, certificate_exp) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Keys whose certificate does not chain to a trusted CSCA are never usable; compromised keys
+    /// are only usable for certificates issued before the compromise
+    pub fn is_usable_at(&self, issued_at: Option<i64>) -> bool {
+        if self.is_chain_trusted == Some(false) {
+            return false;
+        }
+        if !self.is_compromised {
+            return true;
+        }
+        match (issued_at, self.compromised_at) {
+            (Some(iat), Some(compromised_at)) => compromised_at > iat,
+            _ => false,
+        }
+    }
+}
+
+/// Source of the trusted keys the verifier checks signatures with
+#[async_trait]
+pub trait KeyProvider: Send {
+    /// Returns every key registered for the signer country (iso alpha3), compromised ones
+    /// included; whether a key is usable for a given certificate is decided by the verifier
+    async fn get_keys(&mut self, country_code: &str) -> anyhow::Result<Vec<TrustedKey>>;
+
+    /// Same as `get_keys` restricted to the keys registered under `kid`; providers backed by a
+    /// store should look the kid up instead of loading the whole country
+    async fn get_keys_by_kid(
+        &mut self,
+        country_code: &str,
+        kid: &str,
+    ) -> anyhow::Result<Vec<TrustedKey>> {
+        Ok(self
+            .get_keys(country_code)
+            .await?
+            .into_iter()
+            .filter(|trusted_key| trusted_key.kid == kid)
+            .collect::<Vec<_>>())
+    }
+}
+
+/// Keys held in memory, e.g. out of a downloaded trust list, to verify offline
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryKeyProvider {
+    keys_by_country: HashMap<String, Vec<TrustedKey>>,
+}
+
+impl InMemoryKeyProvider {
+    pub fn new(trusted_keys: Vec<TrustedKey>) -> Self {
+        let mut keys_by_country: HashMap<String, Vec<TrustedKey>> = HashMap::new();
+        for trusted_key in trusted_keys {
+            keys_by_country
+                .entry(trusted_key.country_code.clone())
+                .or_default()
+                .push(trusted_key);
+        }
+        InMemoryKeyProvider { keys_by_country }
+    }
+}
+
+#[async_trait]
+impl KeyProvider for InMemoryKeyProvider {
+    async fn get_keys(&mut self, country_code: &str) -> anyhow::Result<Vec<TrustedKey>> {
+        Ok(self
+            .keys_by_country
+            .get(country_code)
+            .cloned()
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,21 @@
+//! HC1 (WHO DDCC and EU DCC) certificate decoding and verification, free of any web framework
+//! or database: trusted keys come from a [`key_provider::KeyProvider`], e.g. the
+//! [`key_provider::InMemoryKeyProvider`] filled from a downloaded trust list to verify offline.
+//!
+//! ```ignore
+//! let mut key_provider = InMemoryKeyProvider::new(trusted_keys);
+//! let verification = verify_hc1(&mut key_provider, "HC1:...", now).await?;
+//! ```
+
+pub mod country_code;
+pub mod cwt_claims;
+pub mod ddcc;
+pub mod error;
+pub mod eu_dcc;
+pub mod hc1;
+pub mod key_provider;
+pub mod signer;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod types;
+pub mod x509;
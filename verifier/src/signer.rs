@@ -0,0 +1,130 @@
+use std::io::Write;
+
+use ciborium::value::Value as CborValue;
+use cose::{algs, keys, message::CoseMessage};
+use flate2::{write::ZlibEncoder, Compression};
+use openssl::pkey::{Id, PKey, Private};
+
+use crate::{
+    cwt_claims::{CWT_EXP, CWT_IAT, CWT_ISS},
+    ddcc::DDCC_CLAIM,
+    eu_dcc::HCERT_CLAIM,
+    x509::X509Utils,
+};
+
+/// Signs CWT payloads into HC1 (COSE_Sign1, zlib, base45) certificates with a DSC and its
+/// private key; meant to produce test vectors and pilot certificates
+pub struct Hc1Signer {
+    /// DSC matching the private key, used to compute the `kid` and the COSE key
+    pem: String,
+    private_key: PKey<Private>,
+}
+
+impl Hc1Signer {
+    /// Fails when the private key does not belong to the certificate
+    pub fn new(pem: String, private_key: PKey<Private>) -> anyhow::Result<Self> {
+        let pem = X509Utils::format_pem(pem);
+        let certificate = openssl::x509::X509::from_pem(pem.as_bytes())?;
+        if !private_key.public_eq(certificate.public_key()?.as_ref()) {
+            return Err(anyhow::anyhow!(
+                "Signer private key does not match the signer certificate"
+            ));
+        }
+        Ok(Hc1Signer { pem, private_key })
+    }
+
+    pub fn get_pem(&self) -> &str {
+        &self.pem
+    }
+
+    pub fn get_kid(&self) -> anyhow::Result<String> {
+        X509Utils::get_kid_string_from_pem(self.pem.clone())
+    }
+
+    /// COSE algorithm and signing key out of the certificate and private key
+    fn get_signing_key(&self) -> anyhow::Result<(i32, keys::CoseKey)> {
+        let jwk = X509Utils::get_jwk_from_pem(self.pem.clone())?;
+        let (alg, d) = match (self.private_key.id(), jwk.crv.as_deref()) {
+            (Id::EC, Some(crv)) => {
+                let (alg, size) = match crv {
+                    "P-256" => (algs::ES256, 32),
+                    "P-384" => (algs::ES384, 48),
+                    "P-521" => (algs::ES512, 66),
+                    _ => return Err(anyhow::anyhow!("Unsupported curve {}", crv)),
+                };
+                let d = self
+                    .private_key
+                    .ec_key()?
+                    .private_key()
+                    .to_vec_padded(size)?;
+                (alg, d)
+            }
+            (Id::ED25519, _) => (algs::EDDSA, self.private_key.raw_private_key()?),
+            (id, _) => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported signer key type {:?}, use an EC or Ed25519 key",
+                    id
+                ))
+            }
+        };
+        let mut key = X509Utils::jwk_to_cose_key(&jwk, &alg)?;
+        key.d(d);
+        key.key_ops(vec![keys::KEY_OPS_SIGN, keys::KEY_OPS_VERIFY]);
+        Ok((alg, key))
+    }
+
+    /// Returns the "HC1:" prefixed base45 certificate; alg and kid go in the protected header
+    pub fn sign(&self, payload: Vec<u8>) -> anyhow::Result<String> {
+        let (alg, key) = self.get_signing_key()?;
+        let kid = X509Utils::get_kid_from_pem(self.pem.clone())?;
+
+        let mut sign1 = CoseMessage::new_sign();
+        sign1.header.alg(alg, true, false);
+        sign1.header.kid(kid, true, false);
+        sign1.payload(payload);
+        sign1
+            .key(&key)
+            .map_err(|e| anyhow::anyhow!("Unable to set signing key: {:?}", e))?;
+        sign1
+            .secure_content(None)
+            .map_err(|e| anyhow::anyhow!("Unable to sign payload: {:?}", e))?;
+        sign1
+            .encode(true)
+            .map_err(|e| anyhow::anyhow!("Unable to encode COSE message: {:?}", e))?;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&sign1.bytes)?;
+        let compressed = encoder.finish()?;
+        Ok(format!("HC1:{}", base45::encode(compressed)))
+    }
+}
+
+/// CWT payload: iss, iat, exp and the DDCC (already CBOR) under the hcert claim
+pub fn get_ddcc_cwt_payload(
+    iss: &str,
+    iat: i64,
+    exp: i64,
+    ddcc: CborValue,
+) -> anyhow::Result<Vec<u8>> {
+    let claims = CborValue::Map(vec![
+        (
+            CborValue::Integer(CWT_ISS.try_into()?),
+            CborValue::Text(iss.to_owned()),
+        ),
+        (
+            CborValue::Integer(CWT_EXP.try_into()?),
+            CborValue::Integer(exp.into()),
+        ),
+        (
+            CborValue::Integer(CWT_IAT.try_into()?),
+            CborValue::Integer(iat.into()),
+        ),
+        (
+            CborValue::Integer(HCERT_CLAIM.try_into()?),
+            CborValue::Map(vec![(CborValue::Integer(DDCC_CLAIM.try_into()?), ddcc)]),
+        ),
+    ]);
+    let mut payload = Vec::new();
+    ciborium::ser::into_writer(&claims, &mut payload)?;
+    Ok(payload)
+}
@@ -0,0 +1,162 @@
+//! Certificates and keys for tests, shared with the crates depending on this one through the
+//! `test-support` feature
+
+use openssl::{
+    asn1::Asn1Time,
+    bn::BigNum,
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{Id, PKey, Private},
+    x509::{X509Builder, X509Extension, X509NameBuilder, X509},
+};
+
+pub const DAY: i64 = 24 * 60 * 60;
+
+pub fn ec_key(curve: Nid) -> PKey<Private> {
+    let group = EcGroup::from_curve_name(curve).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+/// Digest matching the signing key: none for Ed25519, SHA-384 and SHA-512 for P-384 and P-521
+fn get_digest(key: &PKey<Private>) -> MessageDigest {
+    match key.id() {
+        Id::ED25519 => MessageDigest::null(),
+        Id::EC => match key.ec_key().unwrap().group().curve_name() {
+            Some(Nid::SECP384R1) => MessageDigest::sha384(),
+            Some(Nid::SECP521R1) => MessageDigest::sha512(),
+            _ => MessageDigest::sha256(),
+        },
+        _ => MessageDigest::sha256(),
+    }
+}
+
+/// X509 v3 certificate for `key`, signed by `issuer` (certificate and key) or self signed
+pub fn build_certificate(
+    key: &PKey<Private>,
+    common_name: &str,
+    issuer: Option<(&X509, &PKey<Private>)>,
+    not_before: i64,
+    not_after: i64,
+    extensions: Vec<X509Extension>,
+) -> X509 {
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("C", "CL").unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut builder = X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    match issuer {
+        Some((issuer_certificate, _)) => builder
+            .set_issuer_name(issuer_certificate.subject_name())
+            .unwrap(),
+        None => builder.set_issuer_name(&name).unwrap(),
+    }
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::from_unix(not_before).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::from_unix(not_after).unwrap())
+        .unwrap();
+    for extension in extensions {
+        builder.append_extension(extension).unwrap();
+    }
+    let signing_key = issuer.map(|(_, issuer_key)| issuer_key).unwrap_or(key);
+    builder.sign(signing_key, get_digest(signing_key)).unwrap();
+    builder.build()
+}
+
+pub fn to_pem(certificate: &X509) -> String {
+    String::from_utf8(certificate.to_pem().unwrap()).unwrap()
+}
+
+/// Self signed DSC for `key`, valid for a year from now, as pem
+pub fn self_signed_pem(key: &PKey<Private>) -> String {
+    let now = chrono::Utc::now().timestamp();
+    to_pem(&build_certificate(
+        key,
+        "DSC-Test",
+        None,
+        now,
+        now + 365 * DAY,
+        vec![],
+    ))
+}
+
+/// RSA DSCs, the first one flattened on a single line
+pub fn get_rsa_pem_test_keys() -> Option<Vec<String>> {
+    let mut pem_keys = Vec::new();
+    let lacchain_cert = "-----BEGIN CERTIFICATE-----
+    MIIErTCCApWgAwIBAgIUVchUxtzzkaaCN7uGIY+YP24A8iIwDQYJKoZIhvcNAQEN
+    BQAwZDELMAkGA1UEBhMCQ0wxETAPBgNVBAgMCFNhbnRpYWdvMSwwKgYDVQQKDCNN
+    aW5pc3RyeSBvZiBIZWFsdGggLSBMQUNQYXNzIC0gRGVtbzEUMBIGA1UEAwwLTW9I
+    X0xQX0RlbW8wHhcNMjMwOTI1MTk0MjQ0WhcNMjUwMjA2MTk0MjQ0WjBkMQswCQYD
+    VQQGEwJDTDERMA8GA1UECAwIU2FudGlhZ28xLDAqBgNVBAoMI01pbmlzdHJ5IG9m
+    IEhlYWx0aCAtIExBQ1Bhc3MgLSBEZW1vMRQwEgYDVQQDDAtNb0hfTFBfRGVtbzCC
+    ASIwDQYJKoZIhvcNAQEBBQADggEPADCCAQoCggEBAK8cXWc+j6PkqEwZJyEuGlAs
+    OeHoq0CeSFCQ92ZWtX+VmRcYaaOeTcR2ZQQaVUKVMxbUHm+1DLD2XerE9Amg6S75
+    ILgwUGI10xIWrEt43ZwI4d3kOvyItNxhOrMAsM6sF3vdVSfbouhXPU13wwbOGpKk
+    W/S0YjxzM/HVt7hP82ImvJ6TOmyA0QLIGSbamxWuB+YJnl646AD2lqeJcZajzUYs
+    +hes4ShbjBRp4AspDFPyY8IHqBidDmwKcRrWCmtK4rGK8Gv7ryOacdY8YxvOUqml
+    mnQGlUTXV8Y9OCriIGYmoNG/U2VX5IHiHsXN7rxIycaezQBkXAzqyJ2AVVqkXucC
+    AwEAAaNXMFUwCQYDVR0TBAIwADAdBgNVHQ4EFgQUS+aOmyNJE8QDmDJclQ+NWeLV
+    /1AwCwYDVR0PBAQDAgeAMBwGA1UdEQQVMBOHBAECAwSCC215LmRucy5uYW1lMA0G
+    CSqGSIb3DQEBDQUAA4ICAQBnIlGge+PczNYSZIzQgGrtKCL2uKp7eR6MLSuVOoKg
+    0ewI9bMt6093/lcyNKO2XttvkjWa+pIZ6jfh8psnv9JQpXCqSH35vfFP6pc9/dFJ
+    FDRG9Nw+e3vx567wE50YhW1TQQcsKycXz8HZjPNryZH1drtLsLkORqRzH+jkWp4d
+    SQ8YYvoC6N4u6zTDI8FCyfcoQL7+mTmwAjYAl5fvwlgmkvNZeZ31JrWXcNx1PvJv
+    9OuaRdbLUHwPwWBUKwDBdO06XctXxGT221lUIiymkU/gAr8QJP25HM4wtMhCk9i9
+    jeRxzGIM71Uq7Q+EjSasfXEfQbbsTOa2NSOw/EuZARve2qspHQCYNAq1SWAeBQyU
+    lPuEcFZgdTGyKPVGtoqIHvJlt0Gobm0m0A46Om1UTG7b82fsA44gduX1YU+Xp19q
+    b+hGU9u07aWLEbH0sXiuitZzrmI7R6koyhR7ZZ9/X+apVWg+ICcC3uhIrk/BCTE+
+    PkwD2Iw79hIFuO58PMw6F4+HusQz8XfT5Z+KiZGoazQ0HOk2NVnHGjXmZ8OMZcmu
+    Rm3OlEhGS9C60U/r7vishcAC42AupfysH60nRLCc3l5iCGVSjUyiZ+PYXpxrbqgL
+    0/5iKtbVWjCyT7vUckwgmKcOki/gNmSpqyhyhMFiu7MwkxwXcydZCuB8y4P1F+z4
+    WA==
+    -----END CERTIFICATE-----";
+    let begin = "BEGIN CERTIFICATE";
+    let end = "END CERTIFICATE";
+    let mut lacchain_cert = lacchain_cert.replace("\n", "");
+    lacchain_cert.retain(|c| !c.is_whitespace());
+    let lacchain_cert = lacchain_cert
+        .replace("BEGINCERTIFICATE", begin)
+        .replace("ENDCERTIFICATE", end);
+    let create_cert = "-----BEGIN CERTIFICATE-----
+    MIIErTCCApWgAwIBAgIUVchUxtzzkaaCN7uGIY+YP24A8iIwDQYJKoZIhvcNAQENBQAwZDELMAkGA1UEBhMCQ0wxETAPBgNVBAgMCFNhbnRpYWdvMSwwKgYDVQQKDCNNaW5pc3RyeSBvZiBIZWFsdGggLSBMQUNQYXNzIC0gRGVtbzEUMBIGA1UEAwwLTW9IX0xQX0RlbW8wHhcNMjMwOTI1MTk0MjQ0WhcNMjUwMjA2MTk0MjQ0WjBkMQswCQYDVQQGEwJDTDERMA8GA1UECAwIU2FudGlhZ28xLDAqBgNVBAoMI01pbmlzdHJ5IG9mIEhlYWx0aCAtIExBQ1Bhc3MgLSBEZW1vMRQwEgYDVQQDDAtNb0hfTFBfRGVtbzCCASIwDQYJKoZIhvcNAQEBBQADggEPADCCAQoCggEBAK8cXWc+j6PkqEwZJyEuGlAsOeHoq0CeSFCQ92ZWtX+VmRcYaaOeTcR2ZQQaVUKVMxbUHm+1DLD2XerE9Amg6S75ILgwUGI10xIWrEt43ZwI4d3kOvyItNxhOrMAsM6sF3vdVSfbouhXPU13wwbOGpKkW/S0YjxzM/HVt7hP82ImvJ6TOmyA0QLIGSbamxWuB+YJnl646AD2lqeJcZajzUYs+hes4ShbjBRp4AspDFPyY8IHqBidDmwKcRrWCmtK4rGK8Gv7ryOacdY8YxvOUqmlmnQGlUTXV8Y9OCriIGYmoNG/U2VX5IHiHsXN7rxIycaezQBkXAzqyJ2AVVqkXucCAwEAAaNXMFUwCQYDVR0TBAIwADAdBgNVHQ4EFgQUS+aOmyNJE8QDmDJclQ+NWeLV/1AwCwYDVR0PBAQDAgeAMBwGA1UdEQQVMBOHBAECAwSCC215LmRucy5uYW1lMA0GCSqGSIb3DQEBDQUAA4ICAQBnIlGge+PczNYSZIzQgGrtKCL2uKp7eR6MLSuVOoKg0ewI9bMt6093/lcyNKO2XttvkjWa+pIZ6jfh8psnv9JQpXCqSH35vfFP6pc9/dFJFDRG9Nw+e3vx567wE50YhW1TQQcsKycXz8HZjPNryZH1drtLsLkORqRzH+jkWp4dSQ8YYvoC6N4u6zTDI8FCyfcoQL7+mTmwAjYAl5fvwlgmkvNZeZ31JrWXcNx1PvJv9OuaRdbLUHwPwWBUKwDBdO06XctXxGT221lUIiymkU/gAr8QJP25HM4wtMhCk9i9jeRxzGIM71Uq7Q+EjSasfXEfQbbsTOa2NSOw/EuZARve2qspHQCYNAq1SWAeBQyUlPuEcFZgdTGyKPVGtoqIHvJlt0Gobm0m0A46Om1UTG7b82fsA44gduX1YU+Xp19qb+hGU9u07aWLEbH0sXiuitZzrmI7R6koyhR7ZZ9/X+apVWg+ICcC3uhIrk/BCTE+PkwD2Iw79hIFuO58PMw6F4+HusQz8XfT5Z+KiZGoazQ0HOk2NVnHGjXmZ8OMZcmuRm3OlEhGS9C60U/r7vishcAC42AupfysH60nRLCc3l5iCGVSjUyiZ+PYXpxrbqgL0/5iKtbVWjCyT7vUckwgmKcOki/gNmSpqyhyhMFiu7MwkxwXcydZCuB8y4P1F+z4WA==
+    -----END CERTIFICATE-----";
+    pem_keys.push(lacchain_cert.to_owned());
+    pem_keys.push(create_cert.to_owned());
+    Some(pem_keys)
+}
+
+/// P-256 DSC without extended key usage, kid `CWKd9M24nDY=`
+pub fn get_p256_pem_test_keys() -> Option<Vec<String>> {
+    let mut pem_keys = Vec::new();
+    let lacchain_cert = "-----BEGIN CERTIFICATE-----
+    MIIB8TCCAZagAwIBAgIUVMPmb9VzhvWhfBQLcjG7yS6+Py4wCgYIKoZIzj0EAwQw
+    SDELMAkGA1UEBhMCVVMxCzAJBgNVBAgMAkNBMRswGQYDVQQKDBJNaW5pc3RyeSBP
+    ZiBIZWFsdGgxDzANBgNVBAMMBkNBLU1vSDAeFw0yMzA5MjYwNDMwMjFaFw0yNTAy
+    MDcwNDMwMjFaME8xCzAJBgNVBAYTAlVTMQswCQYDVQQIDAJDQTEhMB8GA1UECgwY
+    RFNDIC0gTWluaXN0cnkgb2YgSGVhbHRoMRAwDgYDVQQDDAdEU0MtTW9IMFkwEwYH
+    KoZIzj0CAQYIKoZIzj0DAQcDQgAEWY9cYJMCATULyyMS8WRtZao09HnBotms6ynA
+    eF1dJ471FiGPWp5AjpRmd2pnHnkLHAxbdTEUYhFRwVsowsY4SaNXMFUwCQYDVR0T
+    BAIwADAdBgNVHQ4EFgQU+SB2R0Cff1Vf6Gf9M5k25Nu6JqMwCwYDVR0PBAQDAgeA
+    MBwGA1UdEQQVMBOHBAECAwSCC215LmRucy5uYW1lMAoGCCqGSM49BAMEA0kAMEYC
+    IQD8JKiU8LB+saxWpbjvAwkGghYjKwSL3B9X/VKeZin3EQIhAPDiuOvM9G9W5ger
+    Yz/thKgQfKOtQS9JbgASgQSCeW4i
+    -----END CERTIFICATE-----";
+    let begin = "BEGIN CERTIFICATE";
+    let end = "END CERTIFICATE";
+    let mut lacchain_cert = lacchain_cert.replace("\n", "");
+    lacchain_cert.retain(|c| !c.is_whitespace());
+    let lacchain_cert = lacchain_cert
+        .replace("BEGINCERTIFICATE", begin)
+        .replace("ENDCERTIFICATE", end);
+    pem_keys.push(lacchain_cert.to_owned());
+    Some(pem_keys)
+}
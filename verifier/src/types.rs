@@ -0,0 +1,3 @@
+pub mod eu_dcc;
+pub mod hc1;
+pub mod jwk;
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+// reference: https://github.com/ehn-dcc-development/eu-dcc-schema
+// fields are read from the short names used in the CBOR payload (aliases) and exposed in camelCase
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EuDigitalCovidCertificate {
+    #[serde(alias = "ver")]
+    pub version: String,
+    #[serde(alias = "nam")]
+    pub name: EuDccName,
+    #[serde(alias = "dob")]
+    pub date_of_birth: String,
+    #[serde(alias = "v", default, skip_serializing_if = "Option::is_none")]
+    pub vaccinations: Option<Vec<EuDccVaccination>>,
+    #[serde(alias = "t", default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<Vec<EuDccTest>>,
+    #[serde(alias = "r", default, skip_serializing_if = "Option::is_none")]
+    pub recoveries: Option<Vec<EuDccRecovery>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EuDccName {
+    #[serde(alias = "fn", default, skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    #[serde(alias = "fnt")]
+    pub standardised_family_name: String,
+    #[serde(alias = "gn", default, skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(alias = "gnt", default, skip_serializing_if = "Option::is_none")]
+    pub standardised_given_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EuDccVaccination {
+    #[serde(alias = "tg")]
+    pub target_disease: String,
+    #[serde(alias = "vp")]
+    pub vaccine: String,
+    #[serde(alias = "mp")]
+    pub medicinal_product: String,
+    #[serde(alias = "ma")]
+    pub manufacturer: String,
+    #[serde(alias = "dn")]
+    pub dose_number: u8,
+    #[serde(alias = "sd")]
+    pub total_doses: u8,
+    #[serde(alias = "dt")]
+    pub date: String,
+    #[serde(alias = "co")]
+    pub country: String,
+    #[serde(alias = "is")]
+    pub issuer: String,
+    #[serde(alias = "ci")]
+    pub certificate_identifier: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EuDccTest {
+    #[serde(alias = "tg")]
+    pub target_disease: String,
+    #[serde(alias = "tt")]
+    pub test_type: String,
+    #[serde(alias = "nm", default, skip_serializing_if = "Option::is_none")]
+    pub test_name: Option<String>,
+    #[serde(alias = "ma", default, skip_serializing_if = "Option::is_none")]
+    pub test_device: Option<String>,
+    #[serde(alias = "sc")]
+    pub sample_collected_at: String,
+    #[serde(alias = "tr")]
+    pub test_result: String,
+    #[serde(alias = "tc", default, skip_serializing_if = "Option::is_none")]
+    pub testing_centre: Option<String>,
+    #[serde(alias = "co")]
+    pub country: String,
+    #[serde(alias = "is")]
+    pub issuer: String,
+    #[serde(alias = "ci")]
+    pub certificate_identifier: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EuDccRecovery {
+    #[serde(alias = "tg")]
+    pub target_disease: String,
+    #[serde(alias = "fr")]
+    pub first_positive_result: String,
+    #[serde(alias = "co")]
+    pub country: String,
+    #[serde(alias = "is")]
+    pub issuer: String,
+    #[serde(alias = "df")]
+    pub valid_from: String,
+    #[serde(alias = "du")]
+    pub valid_until: String,
+    #[serde(alias = "ci")]
+    pub certificate_identifier: String,
+}
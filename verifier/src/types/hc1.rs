@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::eu_dcc::EuDigitalCovidCertificate;
+
+/// Outcome of the verification of a HC1 certificate
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Hc1Verification {
+    pub is_valid: bool,
+    /// key identifier (base64) of the key that verified the signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    /// outcome of every check performed; `is_valid` is true only if none of them failed
+    pub checks: Vec<VerificationCheck>,
+    /// tells which of `ddccCoreDataSet` or `euDcc` carries the certificate content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_type: Option<PayloadType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ddcc_core_data_set: Option<DdccCoreDataSet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eu_dcc: Option<EuDigitalCovidCertificate>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadType {
+    /// WHO DDCC:VS core data set
+    Ddcc,
+    /// EU Digital COVID Certificate (hcert claim -260, key 1)
+    EuDcc,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum CheckType {
+    Decoding,
+    Schema,
+    KeyTrust,
+    Signature,
+    CompromisedKey,
+    /// the signing key extended key usage allows the certificate type
+    KeyUsage,
+    KeyExpiry,
+    PayloadExpiry,
+    Issuer,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// the check could not be evaluated (e.g. the claim it relies on is absent)
+    Skipped,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CheckCode {
+    Ok,
+    NotEvaluated,
+    InvalidBase45,
+    InvalidNumericEncoding,
+    InvalidJws,
+    UnsupportedAlgorithm,
+    InvalidJson,
+    InvalidCertificate,
+    InvalidCompression,
+    InvalidCose,
+    InvalidCwt,
+    InvalidSchema,
+    SignerCountryNotFound,
+    KeyNotFound,
+    InvalidSignature,
+    KeyCompromised,
+    KeyCompromisedAfterIssuance,
+    CertificateTypeNotAllowed,
+    SignedAfterKeyExpiry,
+    PayloadExpired,
+    IssuerCountryMismatch,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationCheck {
+    pub check: CheckType,
+    pub status: CheckStatus,
+    pub code: CheckCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl VerificationCheck {
+    pub fn passed(check: CheckType) -> Self {
+        VerificationCheck {
+            check,
+            status: CheckStatus::Passed,
+            code: CheckCode::Ok,
+            kid: None,
+            country: None,
+            message: None,
+        }
+    }
+
+    pub fn failed(check: CheckType, code: CheckCode, message: &str) -> Self {
+        VerificationCheck {
+            check,
+            status: CheckStatus::Failed,
+            code,
+            kid: None,
+            country: None,
+            message: Some(message.to_owned()),
+        }
+    }
+
+    pub fn skipped(check: CheckType, message: &str) -> Self {
+        VerificationCheck {
+            check,
+            status: CheckStatus::Skipped,
+            code: CheckCode::NotEvaluated,
+            kid: None,
+            country: None,
+            message: Some(message.to_owned()),
+        }
+    }
+
+    pub fn with_kid(mut self, kid: Option<String>) -> Self {
+        self.kid = kid;
+        self
+    }
+
+    pub fn with_country(mut self, country: Option<String>) -> Self {
+        self.country = country;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DdccCoreDataSet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vaccination: Option<Vaccination>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_result: Option<TestResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<Recovery>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<Certificate>,
+    /// fields not part of the core data set, kept as found in the payload
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Vaccination {
+    pub date: String,
+    pub dose: u8,
+    pub vaccine: CodeSystem,
+    pub country: CodeSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maholder: Option<CodeSystem>,
+    pub lot: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub centre: Option<String>,
+    pub brand: CodeSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<CodeSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_doses: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub practitioner: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disease: Option<CodeSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_dose: Option<String>,
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+// reference: https://worldhealthorganization.github.io/ddcc/StructureDefinition-DDCCCoreDataSet.TR.html
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TestResult {
+    pub pathogen: CodeSystem,
+    #[serde(rename = "type")]
+    pub test_type: CodeSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brand: Option<CodeSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<CodeSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<CodeSystem>,
+    pub date: String,
+    pub result: CodeSystem,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub centre: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<CodeSystem>,
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Recovery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disease: Option<CodeSystem>,
+    /// date of the first positive test result
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<CodeSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub centre: Option<String>,
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    pub hcid: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<Period>,
+    pub issuer: Identifier,
+    pub version: String,
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSystem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Identifier {
+    pub identifier: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Value {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Period {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
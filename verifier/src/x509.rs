@@ -0,0 +1,847 @@
+use base64::{engine::general_purpose, Engine};
+use cose::keys::{self, CoseKey};
+use crypto::{digest::Digest, sha2::Sha256};
+use log::debug;
+use std::fmt;
+use x509_certificate::{rfc5280, X509Certificate};
+
+use crate::{error::CertificateError, types::jwk::Jwk};
+
+pub struct X509Utils {}
+
+/// Certificate types a DSC may be restricted to through its extended key usage
+/// (reference: https://github.com/ehn-dcc-development/eu-dcc-hcert-spec)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DccCertificateType {
+    Test,
+    Vaccination,
+    Recovery,
+}
+
+impl DccCertificateType {
+    /// DER encoded OIDs 1.3.6.1.4.1.1847.2021.1.1 (test), .2 (vaccination) and .3 (recovery)
+    const OID_PREFIX: [u8; 10] = [0x2b, 0x06, 0x01, 0x04, 0x01, 0x8e, 0x37, 0x8f, 0x65, 0x01];
+
+    pub fn from_oid(oid: &[u8]) -> Option<Self> {
+        match oid.strip_prefix(&Self::OID_PREFIX[..]) {
+            Some([0x01]) => Some(DccCertificateType::Test),
+            Some([0x02]) => Some(DccCertificateType::Vaccination),
+            Some([0x03]) => Some(DccCertificateType::Recovery),
+            _ => None,
+        }
+    }
+
+    /// short code as used for the `public_key.certificate_types` column
+    pub fn code(&self) -> &'static str {
+        match self {
+            DccCertificateType::Test => "t",
+            DccCertificateType::Vaccination => "v",
+            DccCertificateType::Recovery => "r",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "t" => Some(DccCertificateType::Test),
+            "v" => Some(DccCertificateType::Vaccination),
+            "r" => Some(DccCertificateType::Recovery),
+            _ => None,
+        }
+    }
+
+    /// comma separated codes, e.g. "t,v"
+    pub fn to_codes(types: &[DccCertificateType]) -> String {
+        types
+            .iter()
+            .map(|certificate_type| certificate_type.code())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn from_codes(codes: &str) -> Vec<DccCertificateType> {
+        codes.split(',').filter_map(Self::from_code).collect()
+    }
+}
+
+impl fmt::Display for DccCertificateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DccCertificateType::Test => write!(f, "test"),
+            DccCertificateType::Vaccination => write!(f, "vaccination"),
+            DccCertificateType::Recovery => write!(f, "recovery"),
+        }
+    }
+}
+
+impl X509Utils {
+    const BEGIN: &'static str = "-----BEGIN CERTIFICATE-----";
+    const END: &'static str = "-----END CERTIFICATE-----";
+    /// number of bytes taken from the certificate SHA-256 digest to build the key identifier (kid)
+    const KID_LENGTH: usize = 8;
+    /// size in bytes of a P-521 coordinate
+    const P521_COORDINATE_SIZE: i32 = 66;
+    /// DER encoded OID of the basic constraints extension (2.5.29.19)
+    const BASIC_CONSTRAINTS_OID: [u8; 3] = [0x55, 0x1d, 0x13];
+    /// DER encoded OID of the key usage extension (2.5.29.15)
+    const KEY_USAGE_OID: [u8; 3] = [0x55, 0x1d, 0x0f];
+    /// DER encoded OID of the extended key usage extension (2.5.29.37)
+    const EXTENDED_KEY_USAGE_OID: [u8; 3] = [0x55, 0x1d, 0x25];
+    /// key usage bits, as read from the first two bytes of the extension bit string
+    pub const KEY_USAGE_DIGITAL_SIGNATURE: u16 = 0x8000;
+    pub const KEY_USAGE_KEY_CERT_SIGN: u16 = 0x0400;
+    /// returns a string pem certificate that contains "-----BEGIN CERTIFICATE-----" and "-----END CERTIFICATE-----"
+    /// Trims whitespaces and break lines
+    pub fn format_pem(pem_candidate: String) -> String {
+        let begin = "BEGIN CERTIFICATE";
+        let end = "END CERTIFICATE";
+        let mut pem_cert = pem_candidate.replace("\n", "");
+        pem_cert.retain(|c| !c.is_whitespace());
+        if pem_cert.contains("BEGINCERTIFICATE") {
+            let pem_cert = pem_cert
+                .replace("BEGINCERTIFICATE", begin)
+                .replace("ENDCERTIFICATE", end);
+            return pem_cert;
+        }
+        let mut cert = Vec::new();
+        cert.push(Self::BEGIN);
+        cert.push(&pem_cert);
+        cert.push(Self::END);
+        let pem_cert = cert.join("");
+        pem_cert
+    }
+
+    pub fn get_expiration_from_pem(pem_cert: String) -> anyhow::Result<u64> {
+        let pem_cert = Self::format_pem(pem_cert.clone());
+        match X509Certificate::from_pem(pem_cert) {
+            Ok(x509_key) => {
+                let cert = rfc5280::Certificate::from(x509_key);
+                let validity = cert.tbs_certificate.validity;
+                match validity.not_after {
+                    x509_certificate::asn1time::Time::UtcTime(t1) => {
+                        return Ok(t1.timestamp() as u64);
+                    }
+                    x509_certificate::asn1time::Time::GeneralTime(t2) => {
+                        let s = chrono::DateTime::from(t2);
+                        return Ok(s.timestamp() as u64);
+                    }
+                }
+            }
+            Err(e) => {
+                let message = format!("Get expiration: failed to parse certificate: {:?}", e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+    }
+
+    /// Given a pem certificate this method removes "------BEGIN CERTIFICATE-----" as well as "------ENDCERTIFICATE-----"
+    /// Also removes whitespaces and break lines.
+    pub fn trim_pem(pem_cert: String) -> String {
+        let mut t1 = pem_cert
+            .replace(&Self::BEGIN, "")
+            .replace(&Self::END, "")
+            .replace("\n", "");
+        t1.retain(|c| !c.is_whitespace());
+        t1
+    }
+    pub fn get_jwk_from_pem(pem_cert: String) -> anyhow::Result<Jwk> {
+        let pem_cert = Self::format_pem(pem_cert);
+        let trimmed_for_x5c = Self::trim_pem(pem_cert.clone());
+        let mut x5c = Vec::new();
+        x5c.push(trimmed_for_x5c);
+        let x5c = Some(x5c);
+        let kid = Self::get_kid_string_from_pem(pem_cert.clone()).ok();
+        let ec_jwk = |crv: &str, xy: Vec<Vec<u8>>| Jwk {
+            alg: None,
+            r#use: None,
+            kty: Some("EC".to_owned()),
+            kid: kid.clone(),
+            x5c: x5c.clone(),
+            x5t: None,
+            n: None,
+            e: None,
+            x: Some(base64_url::encode(&xy[0])),
+            y: Some(base64_url::encode(&xy[1])),
+            crv: Some(crv.to_owned()),
+        };
+        match x509_certificate::X509Certificate::from_pem(pem_cert.clone()) {
+            Ok(x509_key) => match x509_key.key_algorithm() {
+                Some(alg) => match alg {
+                    x509_certificate::KeyAlgorithm::Rsa => {
+                        let s = x509_key.rsa_public_key_data();
+                        let rsa_public_key = s.unwrap();
+                        let n: Vec<u8> = rsa_public_key.clone().modulus.into_bytes().to_vec();
+                        let e = rsa_public_key.clone().public_exponent.into_bytes().to_vec();
+                        let jwk = Ok(Jwk {
+                            kty: Some("RSA".to_owned()),
+                            e: Some(base64_url::encode(&e)),
+                            alg: None,
+                            r#use: None,
+                            kid,
+                            x5c,
+                            n: Some(base64_url::encode(&n)),
+                            x: None,
+                            y: None,
+                            crv: None,
+                            x5t: None,
+                        });
+                        jwk
+                    }
+                    x509_certificate::KeyAlgorithm::Ecdsa(curve) => {
+                        let pub_key = x509_key.public_key_data().to_owned();
+                        let pub_key = pub_key.to_vec();
+                        let (crv, coordinate_size) = match curve {
+                            x509_certificate::EcdsaCurve::Secp256r1 => ("P-256", 32),
+                            x509_certificate::EcdsaCurve::Secp384r1 => ("P-384", 48),
+                        };
+                        match Self::get_x_y_with_size(pub_key, coordinate_size) {
+                            Err(e) => {
+                                let message =
+                                    format!("DESCRIPTION (x/y coordinate extraction): {:?}", &e);
+                                debug!("{}", message);
+                                return Err(anyhow::anyhow!(message));
+                            }
+                            Ok(xy) => Ok(ec_jwk(crv, xy)),
+                        }
+                    }
+                    x509_certificate::KeyAlgorithm::Ed25519 => {
+                        let pub_key = x509_key.public_key_data().to_owned();
+                        Ok(Jwk {
+                            alg: None,
+                            r#use: None,
+                            kty: Some("OKP".to_owned()),
+                            kid,
+                            x5c,
+                            x5t: None,
+                            n: None,
+                            e: None,
+                            x: Some(base64_url::encode(&pub_key.to_vec())),
+                            y: None,
+                            crv: Some("Ed25519".to_owned()),
+                        })
+                    }
+                },
+                // P-521 is not among the curves known by x509_certificate
+                None => match Self::get_p521_x_y(&pem_cert) {
+                    Ok(xy) => Ok(ec_jwk("P-521", xy)),
+                    Err(e) => {
+                        let message =
+                            format!("Invalid key algorithm, got 'None', error was: {:?}", e);
+                        debug!("{}", message);
+                        return Err(anyhow::anyhow!(message));
+                    }
+                },
+            },
+            Err(e) => {
+                let message = format!("failed to parse certificate: {:?}", e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+    }
+
+    /// Returns the x and y coordinates of a P-521 certificate public key
+    fn get_p521_x_y(pem_cert: &str) -> anyhow::Result<Vec<Vec<u8>>> {
+        let cert = openssl::x509::X509::from_pem(pem_cert.as_bytes())?;
+        let ec_key = cert.public_key()?.ec_key()?;
+        let group = ec_key.group();
+        if group.curve_name() != Some(openssl::nid::Nid::SECP521R1) {
+            return Err(anyhow::anyhow!(
+                "Unsupported curve {:?}",
+                group.curve_name()
+            ));
+        }
+        let mut ctx = openssl::bn::BigNumContext::new()?;
+        let mut x = openssl::bn::BigNum::new()?;
+        let mut y = openssl::bn::BigNum::new()?;
+        ec_key
+            .public_key()
+            .affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
+        Ok(vec![
+            x.to_vec_padded(Self::P521_COORDINATE_SIZE)?,
+            y.to_vec_padded(Self::P521_COORDINATE_SIZE)?,
+        ])
+    }
+
+    /// Builds a cose key (cose-rust library format) out of a jwk: RSA, EC (P-256, P-384, P-521)
+    /// and OKP (Ed25519) keys are supported
+    pub fn jwk_to_cose_key(jwk: &Jwk, signing_alg: &i32) -> anyhow::Result<CoseKey> {
+        let decode = |value: &Option<String>, name: &str| match value {
+            Some(v) => base64_url::decode(v)
+                .map_err(|e| anyhow::anyhow!("Invalid jwk '{}' member: {:?}", name, e)),
+            None => Err(anyhow::anyhow!("Missing jwk '{}' member", name)),
+        };
+        let mut key = keys::CoseKey::new();
+        match (jwk.kty.as_deref(), jwk.crv.as_deref()) {
+            (Some("RSA"), _) => {
+                key.kty(keys::RSA);
+                key.n(decode(&jwk.n, "n")?);
+                key.e(decode(&jwk.e, "e")?);
+            }
+            (Some("EC"), Some(crv)) => {
+                let crv = match crv {
+                    "P-256" => keys::P_256,
+                    "P-384" => keys::P_384,
+                    "P-521" => keys::P_521,
+                    _ => return Err(anyhow::anyhow!("Unsupported EC curve {}", crv)),
+                };
+                key.kty(keys::EC2);
+                key.x(decode(&jwk.x, "x")?);
+                key.y(decode(&jwk.y, "y")?);
+                key.crv(crv);
+            }
+            (Some("OKP"), Some("Ed25519")) => {
+                key.kty(keys::OKP);
+                key.x(decode(&jwk.x, "x")?);
+                key.crv(keys::ED25519);
+            }
+            (kty, crv) => {
+                let message = format!("Unsupported jwk key type {:?} (crv {:?})", kty, crv);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+        key.alg(*signing_alg);
+        key.key_ops(vec![keys::KEY_OPS_VERIFY]);
+        Ok(key)
+    }
+
+    /// Returns cose keys according to cose-rust library format.
+    /// If some key in the incoming "pem_keys" argument is not valid then it is just ommited.
+    pub fn pem_to_cose_keys(pem_keys: Vec<String>, signing_alg: &i32) -> Option<Vec<CoseKey>> {
+        let cose_keys = pem_keys
+            .into_iter()
+            .filter_map(|pem_key| match Self::get_jwk_from_pem(pem_key.clone()) {
+                Err(e) => {
+                    debug!(
+                        "DESCRIPTION (Public Key Pem Decoding): Parsing to jwk failed: {:?} {:?}",
+                        pem_key, &e
+                    );
+                    None
+                }
+                Ok(jwk) => match Self::jwk_to_cose_key(&jwk, signing_alg) {
+                    Ok(key) => Some(key),
+                    Err(e) => {
+                        debug!("DESCRIPTION (jwk to cose key): {:?}", &e);
+                        None
+                    }
+                },
+            })
+            .collect::<Vec<_>>();
+        Some(cose_keys)
+    }
+
+    /// expect 65 bytes containing 1 byte to indicate full compression, 32 bytes for x coordinate and 64 bytes for y coordinate
+    pub fn get_x(pub_key: Vec<u8>) -> Result<Vec<u8>, CertificateError> {
+        if pub_key.is_empty() || pub_key.len() != 65 {
+            return Err(CertificateError::INVALID);
+        }
+        Ok((1..33)
+            .into_iter()
+            .map(|i| pub_key.get(i).unwrap().to_owned())
+            .collect::<Vec<_>>())
+    }
+
+    pub fn get_y(pub_key: Vec<u8>) -> Result<Vec<u8>, CertificateError> {
+        if pub_key.is_empty() || pub_key.len() != 65 {
+            return Err(CertificateError::INVALID);
+        }
+        Ok((33..65)
+            .into_iter()
+            .map(|i| pub_key.get(i).unwrap().to_owned())
+            .collect::<Vec<_>>())
+    }
+
+    /// Splits an uncompressed EC point (0x04 || x || y) into its coordinates of `coordinate_size`
+    /// bytes each (32 for P-256, 48 for P-384)
+    fn get_x_y_with_size(
+        pub_key: Vec<u8>,
+        coordinate_size: usize,
+    ) -> Result<Vec<Vec<u8>>, CertificateError> {
+        if pub_key.len() != 1 + 2 * coordinate_size || pub_key[0] != 0x04 {
+            return Err(CertificateError::INVALID);
+        }
+        Ok(vec![
+            pub_key[1..1 + coordinate_size].to_vec(),
+            pub_key[1 + coordinate_size..].to_vec(),
+        ])
+    }
+
+    /// given a pem certificate it removes all whitespaces, break lines, header and footer and then decodes this according to base64
+    pub fn get_decoded_pem_bytes(pem_cert: String) -> anyhow::Result<Vec<u8>> {
+        let formated_pem = X509Utils::trim_pem(pem_cert.to_string());
+        let decoded_base64 = general_purpose::STANDARD.decode(formated_pem);
+        match decoded_base64 {
+            Ok(decoded) => Ok(decoded),
+            Err(e) => {
+                let message = format!("Unable to decode pem cert, error was: {:?}", e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+    }
+
+    /// Returns the key identifier (kid) for a pem certificate the DCC way: the first 8 bytes of
+    /// the SHA-256 digest over the DER encoded certificate
+    pub fn get_kid_from_pem(pem_cert: String) -> anyhow::Result<Vec<u8>> {
+        match Self::get_decoded_pem_bytes(pem_cert) {
+            Ok(der) => {
+                let mut h = Sha256::new();
+                h.input(&der);
+                let mut digest = [0u8; 32];
+                h.result(&mut digest);
+                Ok(digest[..Self::KID_LENGTH].to_vec())
+            }
+            Err(e) => {
+                let message = format!("Unable to compute kid from pem cert, error was: {:?}", e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+    }
+
+    /// Same as `get_kid_from_pem` but returns the kid base64 encoded, which is how it is stored
+    pub fn get_kid_string_from_pem(pem_cert: String) -> anyhow::Result<String> {
+        Self::get_kid_from_pem(pem_cert).map(|kid| Self::encode_kid(&kid))
+    }
+
+    /// Returns the certificate SHA-256 thumbprint (`x5t#S256`, reference: https://www.rfc-editor.org/rfc/rfc7517#section-4.9),
+    /// base64url encoded
+    pub fn get_x5t_s256_from_pem(pem_cert: String) -> anyhow::Result<String> {
+        let der = Self::get_decoded_pem_bytes(pem_cert)?;
+        let mut h = Sha256::new();
+        h.input(&der);
+        let mut digest = [0u8; 32];
+        h.result(&mut digest);
+        Ok(base64_url::encode(&digest))
+    }
+
+    /// base64 (standard alphabet) encoding for raw kid bytes as found in COSE headers
+    pub fn encode_kid(kid: &[u8]) -> String {
+        general_purpose::STANDARD.encode(kid)
+    }
+
+    /// Returns the JWK thumbprint (reference: https://www.rfc-editor.org/rfc/rfc7638), base64url encoded.
+    /// This is how SMART Health Cards identify their signing keys (`kid`)
+    pub fn get_jwk_thumbprint(jwk: &Jwk) -> anyhow::Result<String> {
+        // members are taken in lexicographic order, no whitespaces
+        let members = match (jwk.kty.as_deref(), &jwk.crv, &jwk.x, &jwk.y, &jwk.e, &jwk.n) {
+            (Some("EC"), Some(crv), Some(x), Some(y), _, _) => {
+                format!(r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#, crv, x, y)
+            }
+            (Some("RSA"), _, _, _, Some(e), Some(n)) => {
+                format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, e, n)
+            }
+            // reference: https://www.rfc-editor.org/rfc/rfc8037#appendix-A.3
+            (Some("OKP"), Some(crv), Some(x), _, _, _) => {
+                format!(r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#, crv, x)
+            }
+            _ => {
+                let message = "Unable to compute thumbprint, missing jwk members";
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        };
+        let mut h = Sha256::new();
+        h.input(members.as_bytes());
+        let mut digest = [0u8; 32];
+        h.result(&mut digest);
+        Ok(base64_url::encode(&digest))
+    }
+
+    /// JWK thumbprint of the certificate public key, see `get_jwk_thumbprint`
+    pub fn get_jwk_thumbprint_from_pem(pem_cert: String) -> anyhow::Result<String> {
+        Self::get_jwk_thumbprint(&Self::get_jwk_from_pem(pem_cert)?)
+    }
+
+    /// Reads one DER element, returns its tag, its content and the bytes that follow it
+    fn read_der_tlv(der: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+        let (&tag, rest) = der.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (length, rest) = if first & 0x80 == 0 {
+            (first as usize, rest)
+        } else {
+            let n = (first & 0x7f) as usize;
+            if n == 0 || n > 4 || rest.len() < n {
+                return None;
+            }
+            let length = rest[..n]
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            (length, &rest[n..])
+        };
+        if rest.len() < length {
+            return None;
+        }
+        Some((tag, &rest[..length], &rest[length..]))
+    }
+
+    /// Returns the DER encoded value of the extension identified by `oid` (DER encoded), if present
+    pub fn get_extension_value(der_cert: &[u8], oid: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        match X509Certificate::from_der(der_cert) {
+            Ok(x509_key) => {
+                let cert = rfc5280::Certificate::from(x509_key);
+                Ok(cert.tbs_certificate.extensions.and_then(|extensions| {
+                    extensions
+                        .iter()
+                        .find(|extension| extension.id.as_ref() == oid)
+                        .map(|extension| extension.value.to_bytes().to_vec())
+                }))
+            }
+            Err(e) => {
+                let message = format!("Get extension: failed to parse certificate: {:?}", e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+    }
+
+    /// Whether the basic constraints extension marks the certificate as a CA; a certificate without
+    /// the extension is not a CA
+    pub fn is_ca(der_cert: &[u8]) -> anyhow::Result<bool> {
+        let value = match Self::get_extension_value(der_cert, &Self::BASIC_CONSTRAINTS_OID)? {
+            Some(v) => v,
+            None => return Ok(false),
+        };
+        // BasicConstraints ::= SEQUENCE { cA BOOLEAN DEFAULT FALSE, pathLenConstraint INTEGER OPTIONAL }
+        match Self::read_der_tlv(&value) {
+            Some((0x30, content, _)) => match Self::read_der_tlv(content) {
+                Some((0x01, ca, _)) => Ok(ca.first().map(|b| *b != 0).unwrap_or(false)),
+                _ => Ok(false),
+            },
+            _ => {
+                let message = "Invalid basic constraints extension".to_owned();
+                debug!("{}", message);
+                Err(anyhow::anyhow!(message))
+            }
+        }
+    }
+
+    /// Returns the key usage bits (see `KEY_USAGE_*`), `None` if the extension is not present
+    pub fn get_key_usage(der_cert: &[u8]) -> anyhow::Result<Option<u16>> {
+        let value = match Self::get_extension_value(der_cert, &Self::KEY_USAGE_OID)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        // KeyUsage ::= BIT STRING, the first content byte holds the number of unused bits
+        match Self::read_der_tlv(&value) {
+            Some((0x03, content, _)) if !content.is_empty() => {
+                let first = content.get(1).copied().unwrap_or(0) as u16;
+                let second = content.get(2).copied().unwrap_or(0) as u16;
+                Ok(Some((first << 8) | second))
+            }
+            _ => {
+                let message = "Invalid key usage extension".to_owned();
+                debug!("{}", message);
+                Err(anyhow::anyhow!(message))
+            }
+        }
+    }
+
+    /// Returns the certificate types the DSC is restricted to by its extended key usage; `None`
+    /// when the certificate carries none of the DCC OIDs, meaning it may sign any type
+    pub fn get_dcc_certificate_types(
+        der_cert: &[u8],
+    ) -> anyhow::Result<Option<Vec<DccCertificateType>>> {
+        let value = match Self::get_extension_value(der_cert, &Self::EXTENDED_KEY_USAGE_OID)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        // ExtKeyUsageSyntax ::= SEQUENCE SIZE (1..MAX) OF KeyPurposeId (OBJECT IDENTIFIER)
+        let mut remaining = match Self::read_der_tlv(&value) {
+            Some((0x30, content, _)) => content,
+            _ => {
+                let message = "Invalid extended key usage extension".to_owned();
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        };
+        let mut types = Vec::new();
+        while let Some((tag, oid, rest)) = Self::read_der_tlv(remaining) {
+            if tag == 0x06 {
+                if let Some(certificate_type) = DccCertificateType::from_oid(oid) {
+                    types.push(certificate_type);
+                }
+            }
+            remaining = rest;
+        }
+        if types.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(types))
+    }
+
+    /// Same as `get_dcc_certificate_types` for a pem certificate
+    pub fn get_dcc_certificate_types_from_pem(
+        pem_cert: String,
+    ) -> anyhow::Result<Option<Vec<DccCertificateType>>> {
+        let der = Self::get_decoded_pem_bytes(pem_cert)?;
+        Self::get_dcc_certificate_types(&der)
+    }
+
+    pub fn get_pem_from_string_jwk(jwk_str: &str) -> anyhow::Result<String> {
+        match serde_json::from_str::<Jwk>(jwk_str) {
+            Ok(jwk) => match jwk.x5c {
+                Some(x5c) => match x5c.get(0) {
+                    Some(pem_candidate) => return Ok(pem_candidate.to_string()),
+                    None => {
+                        let message = "No fields were found in x5c";
+                        debug!("{}", message);
+                        return Err(anyhow::anyhow!(message));
+                    }
+                },
+                None => {
+                    let message = "Unable to extract x5c from jwk";
+                    debug!("{}", message);
+                    return Err(anyhow::anyhow!(message));
+                }
+            },
+            Err(e) => {
+                let message = format!("Unable to parse string to jwk, error was: {}", &e);
+                debug!("{}", message);
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // use std::println;
+
+    use super::*;
+    use crate::test_support::{
+        build_certificate, ec_key, get_p256_pem_test_keys, get_rsa_pem_test_keys, self_signed_pem,
+        DAY,
+    };
+    #[test]
+    fn get_expiration_from_pem_test() {
+        let pem_keys = get_rsa_pem_test_keys().unwrap();
+        let pem_key = pem_keys.get(0).unwrap();
+        match X509Utils::get_expiration_from_pem(pem_key.to_string()) {
+            Ok(v) => {
+                // println!("Expiration {}", v);
+                assert_eq!(v, 1738870964);
+            }
+            Err(_) => {
+                assert_eq!(true, false);
+            }
+        }
+    }
+
+    #[test]
+    fn get_decoded_pem_bytes_rsa_test() {
+        let pem_keys = get_rsa_pem_test_keys().unwrap();
+        let pem_key = pem_keys.get(1).unwrap();
+        match X509Utils::get_decoded_pem_bytes(pem_key.to_string()) {
+            Ok(_v) => {
+                assert_eq!(true, true);
+            }
+            Err(_e) => {
+                assert_eq!(true, false);
+            }
+        }
+    }
+    #[test]
+    fn get_kid_from_pem_test() {
+        let pem_keys = get_p256_pem_test_keys().unwrap();
+        let pem_key = pem_keys.get(0).unwrap();
+        let kid = X509Utils::get_kid_from_pem(pem_key.to_string()).unwrap();
+        assert_eq!(kid, vec![9, 98, 157, 244, 205, 184, 156, 54]);
+        let kid_str = X509Utils::get_kid_string_from_pem(pem_key.to_string()).unwrap();
+        assert_eq!(kid_str, "CWKd9M24nDY=");
+    }
+
+    #[test]
+    fn get_x5t_s256_from_pem_test() {
+        let pem_key = get_rsa_pem_test_keys().unwrap().remove(0);
+        let x5t = X509Utils::get_x5t_s256_from_pem(pem_key.clone()).unwrap();
+        assert_eq!(x5t.len(), 43);
+        // the kid is the start of the same digest
+        let digest = base64_url::decode(&x5t).unwrap();
+        assert_eq!(
+            digest[..8].to_vec(),
+            X509Utils::get_kid_from_pem(pem_key).unwrap()
+        );
+    }
+
+    #[test]
+    fn get_jwk_thumbprint_test() {
+        // reference: https://www.rfc-editor.org/rfc/rfc7638#section-3.1
+        let jwk = Jwk {
+            alg: Some("RS256".to_owned()),
+            r#use: None,
+            kty: Some("RSA".to_owned()),
+            kid: Some("2011-04-29".to_owned()),
+            x5c: None,
+            x5t: None,
+            n: Some("0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw".to_owned()),
+            e: Some("AQAB".to_owned()),
+            x: None,
+            y: None,
+            crv: None,
+        };
+        assert_eq!(
+            X509Utils::get_jwk_thumbprint(&jwk).unwrap(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+
+        let pem = get_p256_pem_test_keys().unwrap().remove(0);
+        let jwk = X509Utils::get_jwk_from_pem(pem.clone()).unwrap();
+        assert_eq!(
+            X509Utils::get_jwk_thumbprint_from_pem(pem).unwrap(),
+            X509Utils::get_jwk_thumbprint(&jwk).unwrap()
+        );
+    }
+
+    #[test]
+    fn get_basic_constraints_and_key_usage_test() {
+        let pem_keys = get_p256_pem_test_keys().unwrap();
+        let der = X509Utils::get_decoded_pem_bytes(pem_keys.get(0).unwrap().to_string()).unwrap();
+        assert!(!X509Utils::is_ca(&der).unwrap());
+        assert_eq!(
+            X509Utils::get_key_usage(&der).unwrap(),
+            Some(X509Utils::KEY_USAGE_DIGITAL_SIGNATURE)
+        );
+    }
+
+    #[test]
+    fn get_dcc_certificate_types_test() {
+        use openssl::{nid::Nid, x509::extension::ExtendedKeyUsage};
+
+        let key = ec_key(Nid::X9_62_PRIME256V1);
+        let now = chrono::Utc::now().timestamp();
+        let extended_key_usage = ExtendedKeyUsage::new()
+            .client_auth()
+            .other("1.3.6.1.4.1.1847.2021.1.1")
+            .other("1.3.6.1.4.1.1847.2021.1.3")
+            .build()
+            .unwrap();
+        let certificate = build_certificate(
+            &key,
+            "DSC-MoH",
+            None,
+            now,
+            now + 365 * DAY,
+            vec![extended_key_usage],
+        );
+        let der = certificate.to_der().unwrap();
+        let types = X509Utils::get_dcc_certificate_types(&der).unwrap().unwrap();
+        assert_eq!(
+            types,
+            vec![DccCertificateType::Test, DccCertificateType::Recovery]
+        );
+        assert_eq!(DccCertificateType::to_codes(&types), "t,r");
+        assert_eq!(DccCertificateType::from_codes("t,r"), types);
+
+        // bundled DSC has no extended key usage: it may sign any certificate type
+        let pem_keys = get_p256_pem_test_keys().unwrap();
+        assert!(X509Utils::get_dcc_certificate_types_from_pem(
+            pem_keys.get(0).unwrap().to_string()
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    /// signs a COSE_Sign1 message with `signing_key`, then verifies it with the cose key built
+    /// out of the certificate pem
+    fn sign_and_verify(pem: String, signing_key: &CoseKey, alg: i32) -> bool {
+        use cose::message::CoseMessage;
+
+        let mut sign1 = CoseMessage::new_sign();
+        sign1.header.alg(alg, true, false);
+        sign1.header.kid(b"kid".to_vec(), true, false);
+        sign1.payload(b"HC1 payload".to_vec());
+        sign1.key(signing_key).unwrap();
+        sign1.secure_content(None).unwrap();
+        sign1.encode(true).unwrap();
+
+        let mut cose_keys = X509Utils::pem_to_cose_keys(vec![pem], &alg).unwrap();
+        assert_eq!(cose_keys.len(), 1);
+        let mut verify = CoseMessage::new_sign();
+        verify.bytes = sign1.bytes;
+        verify.init_decoder(None).unwrap();
+        verify.key(&cose_keys.remove(0)).unwrap();
+        verify.decode(None, None).is_ok()
+    }
+
+    fn ec_signing_key(
+        key: &openssl::pkey::PKey<openssl::pkey::Private>,
+        jwk: &Jwk,
+        alg: i32,
+        coordinate_size: i32,
+    ) -> CoseKey {
+        let mut cose_key = X509Utils::jwk_to_cose_key(jwk, &alg).unwrap();
+        let d = key
+            .ec_key()
+            .unwrap()
+            .private_key()
+            .to_vec_padded(coordinate_size)
+            .unwrap();
+        cose_key.d(d);
+        cose_key.key_ops(vec![keys::KEY_OPS_SIGN, keys::KEY_OPS_VERIFY]);
+        cose_key
+    }
+
+    #[test]
+    fn es384_test() {
+        let key = ec_key(openssl::nid::Nid::SECP384R1);
+        let pem = self_signed_pem(&key);
+        let jwk = X509Utils::get_jwk_from_pem(pem.clone()).unwrap();
+        assert_eq!(jwk.kty.as_deref(), Some("EC"));
+        assert_eq!(jwk.crv.as_deref(), Some("P-384"));
+        let signing_key = ec_signing_key(&key, &jwk, cose::algs::ES384, 48);
+        assert!(sign_and_verify(pem, &signing_key, cose::algs::ES384));
+    }
+
+    #[test]
+    fn es512_test() {
+        let key = ec_key(openssl::nid::Nid::SECP521R1);
+        let pem = self_signed_pem(&key);
+        let jwk = X509Utils::get_jwk_from_pem(pem.clone()).unwrap();
+        assert_eq!(jwk.kty.as_deref(), Some("EC"));
+        assert_eq!(jwk.crv.as_deref(), Some("P-521"));
+        assert_eq!(
+            base64_url::decode(jwk.x.as_ref().unwrap()).unwrap().len(),
+            66
+        );
+        let signing_key = ec_signing_key(&key, &jwk, cose::algs::ES512, 66);
+        assert!(sign_and_verify(pem, &signing_key, cose::algs::ES512));
+    }
+
+    #[test]
+    fn eddsa_test() {
+        let key = openssl::pkey::PKey::generate_ed25519().unwrap();
+        let pem = self_signed_pem(&key);
+        let jwk = X509Utils::get_jwk_from_pem(pem.clone()).unwrap();
+        assert_eq!(jwk.kty.as_deref(), Some("OKP"));
+        assert_eq!(jwk.crv.as_deref(), Some("Ed25519"));
+        assert_eq!(
+            base64_url::decode(jwk.x.as_ref().unwrap()).unwrap(),
+            key.raw_public_key().unwrap()
+        );
+        let mut signing_key = X509Utils::jwk_to_cose_key(&jwk, &cose::algs::EDDSA).unwrap();
+        signing_key.d(key.raw_private_key().unwrap());
+        signing_key.key_ops(vec![keys::KEY_OPS_SIGN, keys::KEY_OPS_VERIFY]);
+        assert!(sign_and_verify(pem, &signing_key, cose::algs::EDDSA));
+
+        // a signature made with another key does not verify
+        let other_key = openssl::pkey::PKey::generate_ed25519().unwrap();
+        let other_pem = self_signed_pem(&other_key);
+        assert!(!sign_and_verify(other_pem, &signing_key, cose::algs::EDDSA));
+    }
+
+    #[test]
+    fn get_decoded_pem_bytes_p256_test() {
+        let pem_keys = get_p256_pem_test_keys().unwrap();
+        let pem_key = pem_keys.get(0).unwrap();
+        match X509Utils::get_decoded_pem_bytes(pem_key.to_string()) {
+            Ok(_v) => {
+                assert_eq!(true, true);
+            }
+            Err(_e) => {
+                assert_eq!(true, false);
+            }
+        }
+    }
+}